@@ -0,0 +1,42 @@
+//! Compares the map's hashers on small integer and string keys. Run with
+//! `cargo bench --features fxhash,ahash`. The identity hasher only suits integer keys, so it
+//! only runs on those.
+
+use std::{hash::{BuildHasher, Hash}, hint::black_box, time::{Duration, Instant}};
+
+use rust_hashmap::hashmap::{DefaultState, HashMap, hashers::{AHashState, FxBuildHasher, IdentityState}};
+
+const KEYS: usize = 10_000;
+const ROUNDS: u32 = 50;
+
+/// Times putting every key in a fresh map and then getting each of them back, returning the
+/// average time per key.
+fn bench<K: Hash + Eq + Clone, S: BuildHasher>(keys: &[K], build: impl Fn() -> S) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut map = HashMap::with_hasher(build());
+        for key in keys {
+            map.put(key.clone(), ());
+        }
+        for key in keys {
+            black_box(map.get(key));
+        }
+    }
+    start.elapsed() / (ROUNDS * keys.len() as u32)
+}
+
+fn report<K: Hash + Eq + Clone>(name: &str, keys: &[K]) {
+    println!("{name}:");
+    println!("  default {:>8?}", bench(keys, DefaultState::new));
+    println!("  fxhash  {:>8?}", bench(keys, FxBuildHasher::default));
+    println!("  ahash   {:>8?}", bench(keys, AHashState::new));
+}
+
+fn main() {
+    let integers: Vec<u32> = (0..KEYS as u32).collect();
+    report("small integer keys", &integers);
+    println!("  identity{:>8?}", bench(&integers, IdentityState::default));
+
+    let strings: Vec<String> = (0..KEYS).map(|i| format!("key-{i}")).collect();
+    report("string keys", &strings);
+}
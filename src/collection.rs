@@ -0,0 +1,125 @@
+//! A common interface over the crate's containers, so helpers can be written once for all of them.
+
+use std::hash::Hash;
+
+use crate::{hashmap::HashMap, linked_list, persistent_list, set::HashSet};
+
+/// A container whose items can be counted and iterated over by reference.
+pub trait Collection {
+    /// What iterating over the collection yields.
+    type Item<'a> where Self: 'a;
+
+    /// Returns the number of items in the collection. This may take linear time, e.g. for lists.
+    fn len(&self) -> usize;
+
+    /// Returns whether the collection has no items.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over references to the collection's items.
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Item<'_>> + '_>;
+}
+
+impl<K: Hash + Eq, V, S> Collection for HashMap<K, V, S> {
+    type Item<'a> = (&'a K, &'a V) where Self: 'a;
+
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Item<'_>> + '_> {
+        Box::new(HashMap::iter(self))
+    }
+}
+
+impl<T: Hash + Eq> Collection for HashSet<T> {
+    type Item<'a> = &'a T where Self: 'a;
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Item<'_>> + '_> {
+        Box::new(HashSet::iter(self))
+    }
+}
+
+impl<T> Collection for linked_list::LinkedList<T> {
+    type Item<'a> = &'a T where Self: 'a;
+
+    fn len(&self) -> usize {
+        linked_list::LinkedList::iter(self).count()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Item<'_>> + '_> {
+        Box::new(linked_list::LinkedList::iter(self))
+    }
+}
+
+impl<T> Collection for persistent_list::LinkedList<T> {
+    type Item<'a> = &'a T where Self: 'a;
+
+    fn len(&self) -> usize {
+        persistent_list::LinkedList::iter(self).count()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Item<'_>> + '_> {
+        Box::new(persistent_list::LinkedList::iter(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count<C: Collection>(collection: &C) -> usize {
+        Collection::iter(collection).count()
+    }
+
+    fn check<C: Collection>(collection: &C, expected: usize) {
+        assert_eq!(count(collection), expected);
+        assert_eq!(collection.len(), expected);
+        assert_eq!(collection.is_empty(), expected == 0);
+    }
+
+    #[test]
+    fn test_hashmap() {
+        let mut map = HashMap::new();
+        check(&map, 0);
+
+        for i in 0..50 {
+            map.put(i, i);
+        }
+        check(&map, 50);
+    }
+
+    #[test]
+    fn test_hashset() {
+        let mut set = HashSet::new();
+        check(&set, 0);
+
+        set.insert(1);
+        set.insert(2);
+        check(&set, 2);
+    }
+
+    #[test]
+    fn test_linked_list() {
+        let mut list = linked_list::LinkedList::new();
+        check(&list, 0);
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        check(&list, 3);
+    }
+
+    #[test]
+    fn test_persistent_list() {
+        let list = persistent_list::LinkedList::new();
+        check(&list, 0);
+
+        check(&list.prepend(1).prepend(2), 2);
+    }
+}
@@ -2,238 +2,2821 @@
 //! Because it uses separate chaining, its capacity has no bearing on the maximum number
 //! of entries it can hold. However, allowing the load factor to become too high
 //! will decrease the average performance of the map.
+//!
+//! For an alternative which stores every entry in one flat array, see
+//! [OpenHashMap](open_addressing::OpenHashMap).
 
-use std::{hash::{Hash, Hasher}, collections::hash_map::DefaultHasher, mem};
+use std::{hash::{BuildHasher, DefaultHasher, Hash}, collections::{TryReserveError, hash_map::RandomState}, cmp::Ordering, fmt, mem, ops::{AddAssign, Index, RangeBounds}, sync::OnceLock};
 
-use self::options::{Options, ValidatedOptions};
+use self::{equivalent::Equivalent, hashers::IdentityState, options::{Options, OptionsBuilder, OptionsError, INCREMENTAL_RESIZE_STEP, SHRINK_THRESHOLD}};
 
 pub mod iter;
 pub mod iter_mut;
 pub mod into_iter;
+pub mod drain;
+pub mod extract_if;
+pub mod keys;
+pub mod values;
+pub mod values_mut;
+pub mod views;
+pub mod into_keys;
+pub mod into_values;
+pub mod diagnostics;
+pub mod integrity;
+pub mod iter_ordered;
+pub mod iter_sorted;
+pub mod map_entry;
 pub mod options;
+pub mod hashers;
+pub mod equivalent;
+pub mod open_addressing;
+
+/// Creates a [HashMap] with the default options holding the given `key => value` pairs.
+/// Like [HashMap::put], later duplicate keys overwrite earlier ones, e.g.
+/// `hashmap!{"a" => 1, "b" => 2}`.
+#[macro_export]
+macro_rules! hashmap {
+    () => {
+        $crate::hashmap::HashMap::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {
+        $crate::hashmap::HashMap::from([$(($key, $value)),+])
+    };
+}
+
+/// The [BuildHasher] maps use unless they're given another one. Each map is seeded randomly the
+/// first time it hashes a key, so which keys collide can't be predicted ahead of time, while
+/// creating a map stays cheap enough to be a `const fn`. For hashing which is the same every run,
+/// create the map with [HashMap::with_hasher] and a fixed-state [BuildHasher] such as
+/// `BuildHasherDefault<DefaultHasher>`.
+#[derive(Clone, Debug, Default)]
+pub struct DefaultState {
+    state: OnceLock<RandomState>
+}
+
+impl DefaultState {
+    /// Creates a [DefaultState] which hasn't been seeded yet.
+    pub const fn new() -> Self {
+        DefaultState { state: OnceLock::new() }
+    }
+}
+
+impl BuildHasher for DefaultState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        self.state.get_or_init(RandomState::new).build_hasher()
+    }
+}
 
-/// A hash map object.
-pub struct HashMap<K, V> {
-    items: Vec<Vec<Entry<K, V>>>,
+/// A [HashMap] for integer keys which are already well distributed, like IDs or handles, which
+/// skips hashing them. Create one with `IntMap::default()`. See [IdentityHasher](hashers::IdentityHasher).
+pub type IntMap<V> = HashMap<u64, V, IdentityState>;
+
+/// A hash map object. Keys are hashed with hashers built by `S`, see [HashMap::with_hasher].
+pub struct HashMap<K, V, S = DefaultState> {
+    items: Vec<Vec<Slot<K, V>>>,
+    // The table being moved out of during an incremental resize, or empty otherwise. Entries
+    // are in either table, in the bucket their hash picks for that table's capacity.
+    old_items: Vec<Vec<Slot<K, V>>>,
+    // How many of the old table's buckets have been moved in order so far. Buckets after this
+    // may also have been moved early, when a key in them was looked up to be modified.
+    migrated: usize,
     size: usize,
-    options: ValidatedOptions
+    next_order: usize,
+    resize_count: usize,
+    options: Options,
+    // How to order keys in buckets past the treeify threshold. Only available for maps created
+    // with `with_options_ord`.
+    compare: Option<fn(&K, &K) -> Ordering>,
+    hash_builder: S
 }
 
-/// A `(key, value)` pair in the map.
+/// A `(key, value)` pair which can be put in the map, e.g. with [HashMap::from_entries].
 pub struct Entry<K, V> {
-    pub key: K,
-    pub value: V
+    key: K,
+    value: V
 }
 
-fn hash(value: &impl Hash) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    value.hash(&mut hasher);
-    hasher.finish()
+impl<K, V> Entry<K, V> {
+    /// Creates a new [Entry] pairing `key` with `value`.
+    pub fn new(key: K, value: V) -> Self {
+        Entry { key, value }
+    }
+
+    /// Returns a reference to the entry's key.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns a reference to the entry's value.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Splits the entry into its key and value.
+    pub fn into_parts(self) -> (K, V) {
+        (self.key, self.value)
+    }
 }
 
-fn find_key_index(key: &impl Hash, capacity: usize) -> usize {
-    let h = hash(&key);
-    // "as" here is fine since we're truncating the hash with the modulo anyway
-    h as usize % capacity
+/// A `(key, value)` pair as it is stored in a bucket.
+#[derive(Clone)]
+struct Slot<K, V> {
+    key: K,
+    value: V,
+    // The key's hash, so the key never has to be hashed again once it's in the map
+    hash: u64,
+    // Position of this entry among the map's insertions. Only tracked when the map
+    // is insertion ordered, otherwise it is always zero.
+    order: usize
 }
 
-impl<K: Hash + Eq, V> HashMap<K, V> {
-    fn create_backing_vec(capacity: usize) -> Vec<Vec<Entry<K, V>>> {
-        let mut vec = Vec::with_capacity(capacity);
-        vec.resize_with(capacity, Vec::new);
-        vec
-    }
+/// The reasons [HashMap::replace_key] can fail.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplaceKeyError {
+    /// The key being replaced is not in the map.
+    MissingKey,
+    /// The replacement key is already in the map.
+    KeyExists
+}
 
+impl<K, V> HashMap<K, V> {
     /// Creates a new [HashMap] with the default options.
     /// See [options] for more details.
-    pub fn new() -> Self {
-        HashMap::with_options(Options::default().validate().unwrap())
+    ///
+    /// Nothing is allocated until the first entry is put in the map, and since this is a
+    /// `const fn` it can be used to initialize statics.
+    pub const fn new() -> Self {
+        HashMap {
+            items: Vec::new(),
+            old_items: Vec::new(),
+            migrated: 0,
+            size: 0,
+            next_order: 0,
+            resize_count: 0,
+            options: Options::DEFAULT,
+            compare: None,
+            hash_builder: DefaultState::new()
+        }
     }
 
     /// Creates a new [HashMap] with the given options.
     /// See [options] for more details.
-    pub fn with_options(options: ValidatedOptions) -> Self {
-        let capacity = options.initial_capacity();
-        let vec = HashMap::create_backing_vec(capacity);
+    pub fn with_options(options: Options) -> Self {
+        HashMap::with_options_and_hasher(options, DefaultState::default())
+    }
+
+    /// Builds the options from `builder` and creates a new [HashMap] with them, or returns why
+    /// they were invalid. See [options] for more details.
+    pub fn try_with_options(builder: OptionsBuilder) -> Result<Self, OptionsError> {
+        builder.build().map(HashMap::with_options)
+    }
+
+    /// Creates a new [HashMap] with `capacity` buckets and otherwise default options.
+    ///
+    /// Panics if `capacity` is zero. See [HashMap::try_with_capacity] for a non-panicking version.
+    pub fn with_capacity(capacity: usize) -> Self {
+        HashMap::try_with_capacity(capacity).unwrap()
+    }
+
+    /// Creates a new [HashMap] with `capacity` buckets and otherwise default options, or
+    /// returns an error if `capacity` is zero.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, OptionsError> {
+        HashMap::try_with_capacity_and_hasher(capacity, DefaultState::default())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    fn create_backing_vec(capacity: usize) -> Vec<Vec<Slot<K, V>>> {
+        let mut vec = Vec::with_capacity(capacity);
+        vec.resize_with(capacity, Vec::new);
+        vec
+    }
+
+    /// Creates a new [HashMap] with the default options which hashes keys with hashers built by
+    /// `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HashMap::with_options_and_hasher(Options::default(), hash_builder)
+    }
+
+    /// Creates a new [HashMap] with `capacity` buckets and otherwise default options, which
+    /// hashes keys with hashers built by `hash_builder`.
+    ///
+    /// Panics if `capacity` is zero. See [HashMap::try_with_capacity_and_hasher] for a
+    /// non-panicking version.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        HashMap::try_with_capacity_and_hasher(capacity, hash_builder).unwrap()
+    }
+
+    /// Creates a new [HashMap] with `capacity` buckets and otherwise default options, which
+    /// hashes keys with hashers built by `hash_builder`, or returns an error if `capacity` is zero.
+    pub fn try_with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Result<Self, OptionsError> {
+        Options::builder().initial_capacity(capacity).build()
+            .map(|options| HashMap::with_options_and_hasher(options, hash_builder))
+    }
+
+    /// Creates a new [HashMap] with the given options which hashes keys with hashers built by
+    /// `hash_builder`. See [options] for more details.
+    pub fn with_options_and_hasher(options: Options, hash_builder: S) -> Self {
+        let vec = if options.lazy_buckets() {
+            Vec::new()
+        } else {
+            Self::create_backing_vec(options.initial_capacity())
+        };
         HashMap {
             items: vec,
+            old_items: Vec::new(),
+            migrated: 0,
             size: 0,
-            options
+            next_order: 0,
+            resize_count: 0,
+            options,
+            compare: None,
+            hash_builder
+        }
+    }
+
+    /// Returns a reference to the map's [BuildHasher].
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Removes and returns an arbitrary entry from the map, or [None] if it's empty. Which entry
+    /// is removed depends on hashing and should not be relied on.
+    pub fn pop_any(&mut self) -> Option<(K, V)> {
+        // Taking the last entry of a bucket never has to move any others, even when it's treeified
+        let slot = self.items.iter_mut().chain(self.old_items.iter_mut()).find_map(|bucket| bucket.pop())?;
+        self.size -= 1;
+        self.shrink_if_sparse();
+        Some((slot.key, slot.value))
+    }
+
+    /// Picks an entry at random, or returns [None] if the map is empty. Every entry is equally
+    /// likely to be picked, as long as `rng` produces uniformly distributed numbers. Only the
+    /// buckets before the picked entry are visited, and only to check their lengths.
+    pub fn sample<R: FnMut() -> u64>(&self, rng: &mut R) -> Option<(&K, &V)> {
+        if self.size == 0 {
+            return None;
+        }
+
+        // "as" is fine here since the result of the modulo is always less than the map's size
+        let mut remaining = (rng() % self.size as u64) as usize;
+        for bucket in self.items.iter().chain(&self.old_items) {
+            if remaining < bucket.len() {
+                let slot = &bucket[remaining];
+                return Some((&slot.key, &slot.value));
+            }
+            remaining -= bucket.len();
+        }
+        unreachable!("The map's size should match the number of entries in its buckets")
+    }
+
+    /// Consumes the map and builds a new one with the result of applying `f` to each value. Since
+    /// the keys don't change, the new map keeps this map's capacity, options, and bucket layout,
+    /// so no entries need to be rehashed.
+    pub fn map_values<U, F: FnMut(V) -> U>(self, mut f: F) -> HashMap<K, U, S> {
+        self.filter_map(|_, value| Some(f(value)))
+    }
+
+    /// Consumes the map and builds a new one with only the entries for which `predicate` returns
+    /// true. Like [HashMap::map_values], no entries need to be rehashed.
+    pub fn filter<F: FnMut(&K, &V) -> bool>(self, mut predicate: F) -> Self {
+        self.filter_map(|key, value| if predicate(key, &value) { Some(value) } else { None })
+    }
+
+    /// Consumes the map and builds a new one by applying `f` to each entry, keeping the key with
+    /// the new value `f` returns and dropping entries for which it returns [None]. Like
+    /// [HashMap::map_values], no entries need to be rehashed.
+    pub fn filter_map<U, F: FnMut(&K, V) -> Option<U>>(self, mut f: F) -> HashMap<K, U, S> {
+        let mut size = 0;
+        let mut filter_buckets = |buckets: Vec<Vec<Slot<K, V>>>| -> Vec<Vec<Slot<K, U>>> {
+            buckets.into_iter()
+                .map(|bucket| bucket.into_iter()
+                    .filter_map(|slot| {
+                        let value = f(&slot.key, slot.value)?;
+                        size += 1;
+                        Some(Slot { key: slot.key, value, hash: slot.hash, order: slot.order })
+                    })
+                    .collect())
+                .collect()
+        };
+        let items = filter_buckets(self.items);
+        let old_items = filter_buckets(self.old_items);
+        HashMap {
+            items,
+            old_items,
+            migrated: self.migrated,
+            size,
+            next_order: self.next_order,
+            // The new map carries on with any resize in progress, so it counts that resize too
+            resize_count: self.resize_count,
+            options: self.options,
+            compare: self.compare,
+            hash_builder: self.hash_builder
+        }
+    }
+
+    /// Releases the memory held by buckets which are empty, for example because all of their
+    /// entries were removed. This doesn't change the number of buckets or move any entries.
+    pub fn trim_empty_buckets(&mut self) {
+        // Part way through an incremental resize, the old table's buckets hold memory too
+        for bucket in self.items.iter_mut().chain(&mut self.old_items).filter(|bucket| bucket.is_empty()) {
+            bucket.shrink_to_fit();
+        }
+    }
+
+    /// Returns the current number of entries in the hash map.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the hash map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Removes every entry from the hash map. The map keeps its capacity and its buckets'
+    /// allocations.
+    pub fn clear(&mut self) {
+        for bucket in self.items.iter_mut() {
+            bucket.clear();
+        }
+        self.old_items = Vec::new();
+        self.migrated = 0;
+        self.size = 0;
+    }
+
+    /// Returns how many times the map has been resized since it was created, whether by dynamic
+    /// resizing or by calling one of the resize methods directly.
+    pub fn resize_count(&self) -> usize {
+        self.resize_count
+    }
+
+    /// Shrinks the map to the smallest capacity which can hold its entries without exceeding its
+    /// load factor, e.g. after removing most of them. Like [HashMap::resize], every entry has to
+    /// be moved.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the map like [HashMap::shrink_to_fit], but to no less than `min_capacity`. Does
+    /// nothing if the map's capacity is already at or below that.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        if !self.is_allocated() {
+            return;
+        }
+
+        let capacity = self.min_capacity_for(self.size).max(min_capacity);
+        if capacity < self.capacity() {
+            self.resize(capacity);
+        }
+    }
+
+    /// Grows the map, following its growth factor, so that `additional` more entries can be put
+    /// in it without it having to resize again. The map is resized at most once, and not at all
+    /// if it already has room. This applies whether or not dynamic resizing is enabled.
+    pub fn reserve(&mut self, additional: usize) {
+        let capacity = self.capacity_for(self.size + additional);
+        if capacity > self.capacity() {
+            self.resize(capacity);
+        }
+    }
+
+    /// Grows the map like [HashMap::reserve], but returns an error rather than aborting if the
+    /// new buckets can't be allocated, in which case the map is left as it was. Only the backing
+    /// vector is allocated fallibly; buckets still allocate as entries are put in them.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let capacity = self.capacity_for(self.size.saturating_add(additional));
+        if capacity > self.capacity() {
+            let mut new_vec = Vec::new();
+            new_vec.try_reserve_exact(capacity)?;
+            new_vec.resize_with(capacity, Vec::new);
+            self.resize_into(new_vec);
+        }
+        Ok(())
+    }
+
+    /// Returns the options the map is using, including any changes made since it was created.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Resize the hash map to have the number of buckets specified by `capacity`. This is an expensive
+    /// operation because it has to move every entry in the map, though keys aren't hashed again
+    /// since each entry keeps its hash. If the map has dynamic resizing enabled, it will
+    /// automatically resize to maintain the configured load factor.
+    pub fn resize(&mut self, capacity: usize) {
+        self.resize_counted(capacity);
+    }
+
+    /// Resize the hash map like [HashMap::resize], returning the number of entries which had to
+    /// be redistributed. This is always the map's size, but makes the cost of resizing observable.
+    pub fn resize_counted(&mut self, capacity: usize) -> usize {
+        self.resize_into(Self::create_backing_vec(capacity))
+    }
+
+    // Moves every entry into `new_vec`, which must already have all its (empty) buckets, and
+    // makes it the map's backing vector. Returns the number of entries moved.
+    fn resize_into(&mut self, mut new_vec: Vec<Vec<Slot<K, V>>>) -> usize {
+        let mut moved = 0;
+        let old_items = mem::take(&mut self.old_items);
+        self.migrated = 0;
+        for entry in mem::take(&mut self.items).into_iter().chain(old_items).flatten() {
+            let index = self.index_for_hash(entry.hash, new_vec.len());
+            new_vec[index].push(entry);
+            moved += 1;
+        }
+        self.items = new_vec;
+        self.treeify_buckets();
+        self.resize_count += 1;
+        moved
+    }
+
+    /// Resize the hash map like [HashMap::resize], but keep the existing buckets' allocations
+    /// rather than building a fresh backing vector. Entries are moved out of their buckets into a
+    /// single staging vector, after which the emptied buckets (which keep their capacity) are
+    /// refilled. When growing, only the newly added buckets ever need to allocate.
+    pub fn resize_reusing(&mut self, capacity: usize) {
+        let mut staging = Vec::with_capacity(self.size);
+        for bucket in self.items.iter_mut().chain(self.old_items.iter_mut()) {
+            staging.append(bucket);
+        }
+        self.old_items = Vec::new();
+        self.migrated = 0;
+
+        self.items.resize_with(capacity, Vec::new);
+        for slot in staging {
+            let index = self.index_for_hash(slot.hash, capacity);
+            self.items[index].push(slot);
+        }
+        self.treeify_buckets();
+        self.resize_count += 1;
+    }
+
+    fn capacity(&self) -> usize {
+        if self.is_allocated() {
+            self.items.len()
+        } else {
+            self.options.initial_capacity()
+        }
+    }
+
+    // Whether the map's buckets exist yet. They are only missing for lazy maps which have never
+    // had anything inserted.
+    fn is_allocated(&self) -> bool {
+        !self.items.is_empty()
+    }
+
+    // Finds the index of the bucket a key with the given hash belongs in, out of `capacity`
+    // buckets.
+    fn index_for_hash(&self, hash: u64, capacity: usize) -> usize {
+        if self.options.fibonacci_hashing() {
+            return options::fibonacci_index(hash, capacity);
+        }
+        // "as" here is fine since we're truncating the hash with the modulo anyway
+        hash as usize % capacity
+    }
+
+    fn exceeds_threshold(&self, size: usize) -> bool {
+        size as f64 >= (self.capacity() as f64) * self.options.load_factor_at(self.capacity())
+    }
+
+    // Returns the capacity the map would grow to, following its growth factor, in order to hold
+    // `size` entries without exceeding its load factor.
+    fn capacity_for(&self, size: usize) -> usize {
+        let mut capacity = self.capacity();
+        // Stop once the capacity saturates, since it can't grow any further
+        while capacity < usize::MAX && size as f64 >= (capacity as f64) * self.options.load_factor_at(capacity) {
+            capacity = (capacity as f64 * self.options.growth_factor()).ceil() as usize;
+        }
+        capacity
+    }
+
+    // Returns the smallest capacity which can hold `size` entries without exceeding the load
+    // factor.
+    fn min_capacity_for(&self, size: usize) -> usize {
+        // An adaptive load factor only goes down as the capacity goes up, so this only ever has to
+        // grow the capacity until it's enough
+        let mut capacity = 1;
+        loop {
+            let needed = (size as f64 / self.options.load_factor_at(capacity)).floor() as usize + 1;
+            if needed <= capacity {
+                return capacity;
+            }
+            capacity = needed;
+        }
+    }
+
+    // Shrinks the map after entries were removed if it has auto shrinking and dynamic resizing
+    // enabled, and has fallen below the shrink threshold. The map is shrunk by its growth factor
+    // until it's at most half as full as its load factor allows, so it doesn't need to grow again
+    // as soon as a few entries are added, but never below its initial capacity.
+    fn shrink_if_sparse(&mut self) {
+        if !(self.options.auto_shrink() && self.options.dynamic_resizing() && self.is_allocated()) {
+            return;
+        }
+
+        let capacity = self.capacity();
+        let threshold = capacity as f64 * self.options.load_factor_at(capacity);
+        if capacity <= self.options.initial_capacity() || self.size as f64 >= threshold * SHRINK_THRESHOLD {
+            return;
+        }
+
+        let mut target = capacity;
+        loop {
+            let next = (target as f64 / self.options.growth_factor()).floor() as usize;
+            let half_full = self.size as f64 >= next as f64 * self.options.load_factor_at(next) / 2.0;
+            if next < self.options.initial_capacity() || half_full {
+                break;
+            }
+            target = next;
+        }
+
+        if target < capacity {
+            self.resize(target);
+        }
+    }
+
+    // Returns how to compare keys in a bucket of the given length, if it's long enough that it
+    // should be kept sorted.
+    fn treeified(&self, bucket_len: usize) -> Option<fn(&K, &K) -> Ordering> {
+        match (self.compare, self.options.treeify_threshold()) {
+            (Some(compare), Some(threshold)) if bucket_len > threshold => Some(compare),
+            _ => None
+        }
+    }
+
+    // Sorts every bucket which has grown past the treeify threshold, e.g. after a resize.
+    fn treeify_buckets(&mut self) {
+        if let (Some(compare), Some(threshold)) = (self.compare, self.options.treeify_threshold()) {
+            for bucket in self.items.iter_mut().filter(|bucket| bucket.len() > threshold) {
+                bucket.sort_by(|a, b| compare(&a.key, &b.key));
+            }
+        }
+    }
+
+    // Adds a slot to a bucket, keeping the bucket sorted if it's treeified. Doesn't check whether
+    // the key is already present.
+    fn push_slot(&mut self, index: usize, slot: Slot<K, V>) -> &mut Slot<K, V> {
+        let len = self.items[index].len();
+        let was_treeified = self.treeified(len).is_some();
+        let compare = self.treeified(len + 1);
+        let bucket = &mut self.items[index];
+
+        let position = match compare {
+            Some(compare) => {
+                if !was_treeified {
+                    bucket.sort_by(|a, b| compare(&a.key, &b.key));
+                }
+                let position = bucket.binary_search_by(|other| compare(&other.key, &slot.key))
+                    .unwrap_or_else(|position| position);
+                bucket.insert(position, slot);
+                position
+            },
+            None => {
+                bucket.push(slot);
+                len
+            }
+        };
+        &mut bucket[position]
+    }
+
+    // Removes the slot at a position in a bucket, keeping the bucket sorted if it's treeified.
+    fn remove_at(&mut self, index: usize, position: usize) -> Slot<K, V> {
+        if self.treeified(self.items[index].len()).is_some() {
+            self.items[index].remove(position)
+        } else {
+            self.items[index].swap_remove(position)
+        }
+    }
+
+    // Every bucket in the map, including any still in the old table part way through an
+    // incremental resize.
+    fn buckets(&self) -> impl Iterator<Item = &Vec<Slot<K, V>>> {
+        self.items.iter().chain(&self.old_items)
+    }
+
+    // Every entry in the map, in the same order as `buckets`.
+    fn slots(&self) -> impl Iterator<Item = &Slot<K, V>> {
+        self.buckets().flatten()
+    }
+
+    // Consumes the map to produce every entry in it, in the same order as `buckets`.
+    fn into_slots(self) -> impl Iterator<Item = Slot<K, V>> {
+        self.items.into_iter().chain(self.old_items).flatten()
+    }
+
+    // The buckets an entry with `hash` could be in: during an incremental resize, its bucket in
+    // the old table, followed by its bucket in the new one. The map must be allocated.
+    fn candidate_buckets(&self, hash: u64) -> impl Iterator<Item = &Vec<Slot<K, V>>> {
+        let old = self.is_migrating()
+            .then(|| &self.old_items[self.index_for_hash(hash, self.old_items.len())]);
+        old.into_iter().chain(Some(&self.items[self.index_for_hash(hash, self.capacity())]))
+    }
+
+    // Whether the map is part way through an incremental resize.
+    fn is_migrating(&self) -> bool {
+        !self.old_items.is_empty()
+    }
+
+    // Starts growing the map to `capacity` buckets incrementally. The current table becomes the
+    // old one, and its entries are moved over by later operations. A resize which is still in
+    // progress is finished first, though step sizing normally means there won't be one.
+    fn start_migration(&mut self, capacity: usize) {
+        self.finish_migration();
+        self.old_items = mem::replace(&mut self.items, Self::create_backing_vec(capacity));
+        self.resize_count += 1;
+    }
+
+    // Does a step of any incremental resize in progress, also moving the old bucket entries with
+    // `hash` are in, so that afterwards they can only be in the new table.
+    fn migrate_for(&mut self, hash: u64) {
+        if self.is_migrating() {
+            self.migrate_bucket(self.index_for_hash(hash, self.old_items.len()));
+            self.migrate_step();
+        }
+    }
+
+    // Moves the next few buckets of the old table into the new one, dropping the old table once
+    // every bucket has been moved. Steps are usually `INCREMENTAL_RESIZE_STEP` buckets, but get
+    // bigger when there's little room left before the map grows again, so that a move is always
+    // done by the time the next one would start.
+    fn migrate_step(&mut self) {
+        let remaining = self.old_items.len() - self.migrated;
+        let limit = (self.capacity() as f64 * self.options.load_factor_at(self.capacity())).ceil() as usize;
+        // Insertions left before the one which grows the map, each of which does a step first
+        let headroom = limit.saturating_sub(self.size + 1);
+        let step = remaining.div_ceil(headroom + 1).max(INCREMENTAL_RESIZE_STEP);
+        let end = (self.migrated + step).min(self.old_items.len());
+        for old_index in self.migrated..end {
+            self.migrate_bucket(old_index);
+        }
+        self.migrated = end;
+
+        if self.migrated == self.old_items.len() {
+            self.old_items = Vec::new();
+            self.migrated = 0;
+        }
+    }
+
+    // Moves every entry in one of the old table's buckets into the new table.
+    fn migrate_bucket(&mut self, old_index: usize) {
+        for slot in mem::take(&mut self.old_items[old_index]) {
+            let index = self.index_for_hash(slot.hash, self.capacity());
+            self.push_slot(index, slot);
+        }
+    }
+
+    // Finishes any incremental resize in progress, so every entry is in the current table.
+    fn finish_migration(&mut self) {
+        while self.is_migrating() {
+            self.migrate_step();
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Gets a reference to the value corresponding to a key, if it exists. The key may be any
+    /// borrowed form of the map's key type, e.g. `&str` for a map with [String] keys, or any
+    /// other type which is [Equivalent] to it.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V> where Q: Hash + Equivalent<K> + ?Sized {
+        self.find(key).map(|slot| &slot.value)
+    }
+
+    /// Gets references to the key stored in the map and its value, if the key exists. Like
+    /// [HashMap::get], the key may be any borrowed form of the map's key type.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)> where Q: Hash + Equivalent<K> + ?Sized {
+        self.find(key).map(|slot| (&slot.key, &slot.value))
+    }
+
+    /// Looks up each of `keys` in turn like [HashMap::get], yielding their values in the same
+    /// order as the keys.
+    pub fn get_many<'a, Q, I>(&'a self, keys: I) -> impl Iterator<Item = Option<&'a V>> + 'a
+    where Q: Hash + Equivalent<K> + ?Sized + 'a, I: IntoIterator<Item = &'a Q>, I::IntoIter: 'a {
+        keys.into_iter().map(move |key| self.get(key))
+    }
+
+    /// Returns whether the map has an entry for a key. Like [HashMap::get], the key may be any
+    /// borrowed form of the map's key type.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool where Q: Hash + Equivalent<K> + ?Sized {
+        self.find(key).is_some()
+    }
+
+    /// Gets a mutable reference to the value corresponding to a key, if it exists. Like
+    /// [HashMap::get], the key may be any borrowed form of the map's key type.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V> where Q: Hash + Equivalent<K> + ?Sized {
+        let (index, position) = self.locate_borrowed(key);
+        position.map(|position| &mut self.items[index][position].value)
+    }
+
+    /// Puts a `(key, value)` pair in the map. This will overwrite any existing value for the given
+    /// key. Returns the existing value if it exists.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        match self.locate(&key) {
+            (_, index, Some(position)) => Some(mem::replace(&mut self.items[index][position].value, value)),
+            (hash, _, None) => {
+                self.insert_new_hashed(hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Puts a `(key, value)` pair in the map only if the key isn't already present, returning a
+    /// mutable reference to the inserted value. Unlike [HashMap::put], an existing value is never
+    /// overwritten: the rejected key and value are handed back instead.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, (K, V)> {
+        match self.locate(&key) {
+            (_, _, Some(_)) => Err((key, value)),
+            (hash, _, None) => Ok(self.insert_new_hashed(hash, key, value))
+        }
+    }
+
+    /// Puts a `(key, value)` pair in the map only if the key isn't already present, returning
+    /// whether it was inserted. An existing value is left as it is and `value` is dropped. See
+    /// [HashMap::try_insert] to get the rejected value back.
+    pub fn insert_if_absent(&mut self, key: K, value: V) -> bool {
+        self.try_insert(key, value).is_ok()
+    }
+
+    /// Replaces the value for `key` only if the key is already in the map, returning the old
+    /// value. If the key isn't present, nothing is inserted and `value` is handed back as the
+    /// error. Like [HashMap::get], the key may be any borrowed form of the map's key type.
+    pub fn replace<Q>(&mut self, key: &Q, value: V) -> Result<V, V> where Q: Hash + Equivalent<K> + ?Sized {
+        match self.get_mut(key) {
+            Some(existing) => Ok(mem::replace(existing, value)),
+            None => Err(value)
+        }
+    }
+
+    /// Puts every `(key, value)` pair in the map like [HashMap::put]. Rather than resizing
+    /// repeatedly as the map fills up, the map is grown up front to fit the pairs `entries` is
+    /// known to yield. This is the same as [Extend::extend].
+    pub fn put_all(&mut self, entries: impl IntoIterator<Item = (K, V)>) {
+        self.extend(entries);
+    }
+
+    /// Puts a `(key, value)` pair in the map like [HashMap::put], but never resizes the map, even
+    /// if it has dynamic resizing enabled. This saves checking the load factor on every insertion
+    /// when bulk loading a map, but the caller is responsible for sizing the map beforehand, e.g.
+    /// with [HashMap::with_capacity] or [HashMap::resize].
+    pub fn put_no_resize(&mut self, key: K, value: V) -> Option<V> {
+        match self.locate(&key) {
+            (_, index, Some(position)) => Some(mem::replace(&mut self.items[index][position].value, value)),
+            (hash, _, None) => {
+                self.insert_no_resize_hashed(hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Returns the value corresponding to a key, if it exists. Like [HashMap::get], the key may
+    /// be any borrowed form of the map's key type. If the map has auto shrinking enabled, it may
+    /// shrink once enough entries have been removed; see [options::OptionsBuilder::auto_shrink].
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V> where Q: Hash + Equivalent<K> + ?Sized {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the map, returning the stored key and its value if the key was present.
+    /// Like [HashMap::get], the key may be any borrowed form of the map's key type.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)> where Q: Hash + Equivalent<K> + ?Sized {
+        let (index, position) = self.locate_borrowed(key);
+        position.map(|position| {
+            self.size -= 1;
+            let slot = self.remove_at(index, position);
+            self.shrink_if_sparse();
+            (slot.key, slot.value)
+        })
+    }
+
+    /// Adds `amount` to the value for `key`. If the key isn't in the map yet, it is first
+    /// inserted with the value type's default (zero, for numbers).
+    pub fn add_assign(&mut self, key: K, amount: V) where V: AddAssign + Default {
+        self.accumulate(key, V::default, |value| *value += amount);
+    }
+
+    /// Removes every key in `keys` from the map. Returns how many of them were in the map;
+    /// missing keys aren't counted, and a key repeated in `keys` is counted once.
+    pub fn remove_all(&mut self, keys: &[K]) -> usize {
+        keys.iter()
+            .filter(|key| self.pop(*key).is_some())
+            .count()
+    }
+
+    /// Returns the value corresponding to a key, if it exists. Unlike [HashMap::pop], this
+    /// keeps the remaining entries in the key's bucket in their original order, at the cost of
+    /// shifting every entry after it. For the same reason, the map is never shrunk.
+    pub fn remove_stable(&mut self, key: &K) -> Option<V> {
+        let (_, index, position) = self.locate(key);
+        position.map(|position| {
+            self.size -= 1;
+            self.items[index].remove(position).value
+        })
+    }
+
+    /// Returns a mutable reference to the value for `key`, first inserting the value produced by
+    /// `default` if the key isn't in the map yet. Only one lookup is needed either way.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, default: F) -> &mut V {
+        match self.locate(&key) {
+            (_, index, Some(position)) => &mut self.items[index][position].value,
+            (hash, _, None) => self.insert_new_hashed(hash, key, default())
+        }
+    }
+
+    /// Inserts, updates, or removes the value for `key` with a single lookup. `f` is called with
+    /// the key and its current value, if any. If it returns a value, that becomes the key's
+    /// value, otherwise the key is removed from the map. Returns a reference to the key's new
+    /// value, if it has one.
+    pub fn compute<F>(&mut self, key: K, f: F) -> Option<&mut V> where F: FnOnce(&K, Option<V>) -> Option<V> {
+        match self.locate(&key) {
+            (_, index, Some(position)) => self.compute_at(index, position, |key, value| f(key, Some(value))),
+            (hash, _, None) => {
+                let value = f(&key, None)?;
+                Some(self.insert_new_hashed(hash, key, value))
+            }
+        }
+    }
+
+    /// Like [HashMap::compute], but only calls `f` if `key` is already in the map, so it can
+    /// only update or remove the key's value. Like [HashMap::get], the key may be any borrowed
+    /// form of the map's key type.
+    pub fn compute_if_present<Q, F>(&mut self, key: &Q, f: F) -> Option<&mut V>
+    where Q: Hash + Equivalent<K> + ?Sized, F: FnOnce(&K, V) -> Option<V> {
+        match self.locate_borrowed(key) {
+            (index, Some(position)) => self.compute_at(index, position, f),
+            (_, None) => None
+        }
+    }
+
+    /// Updates the value for `key` with `combine`. If the key isn't in the map yet, it is first
+    /// inserted with the value produced by `init`, which `combine` then updates like any other.
+    pub fn accumulate<F, G>(&mut self, key: K, init: F, combine: G)
+    where F: FnOnce() -> V, G: FnOnce(&mut V) {
+        combine(self.get_or_insert_with(key, init));
+    }
+
+    /// Folds `other` into this map, consuming it. Keys only in `other` are inserted as they are.
+    /// For keys in both maps, `resolve` is called with the key, this map's value, and `other`'s
+    /// value, and decides what this map's value becomes, e.g. by adding `other`'s value to it or
+    /// replacing it outright.
+    pub fn merge<T, F>(&mut self, other: HashMap<K, V, T>, mut resolve: F) where F: FnMut(&K, &mut V, V) {
+        for slot in other.into_slots() {
+            match self.locate(&slot.key) {
+                (_, index, Some(position)) => {
+                    let mine = &mut self.items[index][position];
+                    resolve(&mine.key, &mut mine.value, slot.value);
+                },
+                (hash, _, None) => {
+                    self.insert_new_hashed(hash, slot.key, slot.value);
+                }
+            }
         }
     }
 
-    /// Gets a reference to the value corresponding to a key, if it exists.
-    pub fn get(&self, key: &K) -> Option<&V> {
-        let index = find_key_index(&key, self.capacity());
-        let containing_list = &self.items[index];
+    /// Moves the value stored under `old` so that it is stored under `new` instead, without
+    /// touching the value itself. Fails if `old` is not in the map or if `new` already is.
+    pub fn replace_key(&mut self, old: &K, new: K) -> Result<(), ReplaceKeyError> {
+        let (_, old_index, old_position) = self.locate(old);
+        let old_position = old_position.ok_or(ReplaceKeyError::MissingKey)?;
+
+        if self.get(&new).is_some() {
+            return Err(ReplaceKeyError::KeyExists);
+        }
+
+        // Finish with the old bucket before touching the new one, since they may be the same
+        let slot = self.remove_at(old_index, old_position);
+        let hash = self.make_hash(&new);
+        let new_index = self.index_for_hash(hash, self.capacity());
+        self.push_slot(new_index, Slot { key: new, hash, ..slot });
+        Ok(())
+    }
+
+    /// Returns every `(key, value)` pair whose key falls within `range`, sorted by key. Since the
+    /// map doesn't keep its keys in order, this is a linear scan over every entry followed by a
+    /// sort, so it's a convenience rather than an efficient ordered lookup.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Vec<(&K, &V)> where K: Ord {
+        let mut entries: Vec<(&K, &V)> = self.slots()
+            .filter(|slot| range.contains(&slot.key))
+            .map(|slot| (&slot.key, &slot.value))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    /// Checks whether `a` and `b` would be stored in the same bucket, whether or not either is
+    /// actually in the map. This depends on the map's current capacity, so keys that collide
+    /// may stop colliding after a resize, and vice versa.
+    pub fn keys_collide(&self, a: &K, b: &K) -> bool {
+        self.find_key_index(a, self.capacity()) == self.find_key_index(b, self.capacity())
+    }
+
+    /// Checks whether this map holds the same `(key, value)` pairs as `other`, regardless of
+    /// either map's capacity or the order of entries within their buckets. Values are compared
+    /// with their own [PartialEq] implementation.
+    pub fn structural_eq(&self, other: &Self) -> bool where V: PartialEq {
+        self.size() == other.size() && self.slots()
+            .all(|entry| other.get(&entry.key) == Some(&entry.value))
+    }
+
+    /// Pairs up the entries of this map and `other` which share a key, yielding the key along
+    /// with both values. Keys which are only in one of the maps are skipped.
+    pub fn join<'a, W, T: BuildHasher>(&'a self, other: &'a HashMap<K, W, T>) -> impl Iterator<Item = (&'a K, &'a V, &'a W)> + 'a {
+        self.slots()
+            .filter_map(move |slot| other.get(&slot.key).map(|theirs| (&slot.key, &slot.value, theirs)))
+    }
+
+    /// Like [HashMap::join], but also yields the keys which are only in one of the maps, with
+    /// [None] in place of the value from the map missing them. Every key in either map is
+    /// yielded exactly once.
+    pub fn outer_join<'a, W, T: BuildHasher>(&'a self, other: &'a HashMap<K, W, T>)
+    -> impl Iterator<Item = (&'a K, Option<&'a V>, Option<&'a W>)> + 'a {
+        let mine = self.slots()
+            .map(move |slot| (&slot.key, Some(&slot.value), other.get(&slot.key)));
+        let theirs = other.slots()
+            .filter(move |slot| !self.contains_key(&slot.key))
+            .map(|slot| (&slot.key, None, Some(&slot.value)));
+        mine.chain(theirs)
+    }
+
+    /// Yields the entries of this map whose keys are not in `other`. The values in `other` are
+    /// ignored, so the two maps may hold different types of value.
+    pub fn difference_keys<'a, W, T: BuildHasher>(&'a self, other: &'a HashMap<K, W, T>) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        self.slots()
+            .filter(move |slot| !other.contains_key(&slot.key))
+            .map(|slot| (&slot.key, &slot.value))
+    }
+
+    /// Yields the entries of this map whose keys are also in `other`. Like
+    /// [HashMap::difference_keys], the values in `other` are ignored.
+    pub fn intersection_keys<'a, W, T: BuildHasher>(&'a self, other: &'a HashMap<K, W, T>) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        self.slots()
+            .filter(move |slot| other.contains_key(&slot.key))
+            .map(|slot| (&slot.key, &slot.value))
+    }
+
+    /// Returns references to every key whose value equals `value`. Unlike looking up a key,
+    /// this is a linear scan over every entry in the map.
+    pub fn keys_for_value(&self, value: &V) -> Vec<&K> where V: PartialEq {
+        self.slots()
+            .filter(|slot| &slot.value == value)
+            .map(|slot| &slot.key)
+            .collect()
+    }
+
+    /// Changes the map's load factor, e.g. to tighten it before a bulk insert and relax it
+    /// afterwards. If the map has dynamic resizing enabled and is already fuller than the new load
+    /// factor allows, it's grown straight away. Returns an error, leaving the map as it is, if
+    /// `load_factor` isn't greater than zero.
+    pub fn set_load_factor(&mut self, load_factor: f64) -> Result<(), OptionsError> {
+        self.options.set_load_factor(load_factor)?;
+        self.grow_to_threshold();
+        Ok(())
+    }
+
+    /// Turns dynamic resizing on or off. When it's turned on, the map is grown straight away if
+    /// it's fuller than its load factor allows, e.g. after entries were added with it off.
+    pub fn set_dynamic_resizing(&mut self, dynamic_resizing: bool) {
+        self.options.set_dynamic_resizing(dynamic_resizing);
+        self.grow_to_threshold();
+    }
+
+    /// Consumes the map and builds a new one whose keys are the result of applying `f` to each
+    /// key. Every entry is rehashed into the bucket its new key belongs in. The new map is
+    /// created with this map's options.
+    ///
+    /// If `f` maps two keys to equal new keys, the last one visited wins, following the same
+    /// order as [HashMap::iter]. Since that order depends on hashing, which of the values
+    /// survives should not be relied on.
+    pub fn transform_keys<L: Hash + Eq, F: FnMut(K) -> L>(self, mut f: F) -> HashMap<L, V, S> where S: Clone {
+        let mut map = HashMap::with_options_and_hasher(self.options.clone(), self.hash_builder.clone());
+        for slot in self.into_slots() {
+            map.put(f(slot.key), slot.value);
+        }
+        map
+    }
+
+    /// Consumes the map and builds a reverse index of it, mapping each value to its key. The new
+    /// map is created with this map's options.
+    ///
+    /// If several keys share a value, only one of them is kept: like [HashMap::transform_keys],
+    /// the last one visited wins, so which one survives should not be relied on. Use
+    /// [HashMap::invert_grouped] to keep all of them.
+    pub fn invert(self) -> HashMap<V, K, S> where V: Hash + Eq, S: Clone {
+        let mut map = HashMap::with_options_and_hasher(self.options.clone(), self.hash_builder.clone());
+        map.extend(self.into_iter().map(|(key, value)| (value, key)));
+        map
+    }
+
+    /// Consumes the map and builds a reverse index of it, mapping each value to every key which
+    /// had that value. The keys for each value are in no particular order. The new map is created
+    /// with this map's options.
+    pub fn invert_grouped(self) -> HashMap<V, Vec<K>, S> where V: Hash + Eq, S: Clone {
+        let mut map = HashMap::with_options_and_hasher(self.options.clone(), self.hash_builder.clone());
+        for (key, value) in self.into_iter() {
+            map.accumulate(value, Vec::new, |keys| keys.push(key));
+        }
+        map
+    }
+
+    /// Splits the map into `n` independent maps, consuming it. Each entry is placed in the
+    /// map at the index of the bucket it would be in if the map had `n` buckets. Every shard is
+    /// created with this map's options and hasher.
+    ///
+    /// Panics if `n` is zero.
+    pub fn shard(mut self, n: usize) -> Vec<HashMap<K, V, S>> where S: Clone {
+        assert!(n > 0, "Cannot shard a map into zero shards");
+
+        let mut shards: Vec<HashMap<K, V, S>> = (0..n)
+            .map(|_| HashMap {
+                compare: self.compare,
+                ..HashMap::with_options_and_hasher(self.options.clone(), self.hash_builder.clone())
+            })
+            .collect();
+        let old_items = mem::take(&mut self.old_items);
+        for entry in mem::take(&mut self.items).into_iter().chain(old_items).flatten() {
+            let index = self.index_for_hash(entry.hash, n);
+            shards[index].insert_new_hashed(entry.hash, entry.key, entry.value);
+        }
+        shards
+    }
+
+    // Finds the index of the bucket `key` belongs in, out of `capacity` buckets.
+    fn find_key_index<Q: Hash + ?Sized>(&self, key: &Q, capacity: usize) -> usize {
+        self.index_for_hash(self.make_hash(key), capacity)
+    }
+
+    fn make_hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    // Finds the index of the bucket `key` belongs in, and its position in that bucket if present.
+    // Part way through an incremental resize, the key's bucket in the old table is moved over
+    // first, so the key can only be in the new table. The key's hash is returned too, so a
+    // missing key can be inserted without hashing it again.
+    fn locate(&mut self, key: &K) -> (u64, usize, Option<usize>) {
+        let hash = self.make_hash(key);
+        let (index, position) = self.locate_hashed(hash, key);
+        (hash, index, position)
+    }
+
+    // Like `locate`, but for a key whose hash has already been computed.
+    fn locate_hashed(&mut self, hash: u64, key: &K) -> (usize, Option<usize>) {
+        self.migrate_for(hash);
+        let index = self.index_for_hash(hash, self.capacity());
+        if !self.is_allocated() {
+            return (index, None);
+        }
+
+        (index, self.search(&self.items[index], hash, key).0)
+    }
+
+    // Like `locate`, but for any form of the key which is equivalent to it. Since the key's
+    // ordering only applies to `K` itself, treeified buckets are scanned linearly rather than binary searched.
+    fn locate_borrowed<Q>(&mut self, key: &Q) -> (usize, Option<usize>) where Q: Hash + Equivalent<K> + ?Sized {
+        self.locate_borrowed_hashed(self.make_hash(key), key)
+    }
+
+    // Like `locate_borrowed`, but for a key whose hash has already been computed.
+    fn locate_borrowed_hashed<Q>(&mut self, hash: u64, key: &Q) -> (usize, Option<usize>) where Q: Equivalent<K> + ?Sized {
+        self.migrate_for(hash);
+        let index = self.index_for_hash(hash, self.capacity());
+        if !self.is_allocated() {
+            return (index, None);
+        }
+
+        let position = self.items[index].iter().position(|slot| slot.hash == hash && key.equivalent(&slot.key));
+        (index, position)
+    }
+
+    // Finds the entry for any form of the key which is equivalent to it, without modifying the
+    // map. Part way through an incremental resize, that means checking both tables.
+    fn find<Q>(&self, key: &Q) -> Option<&Slot<K, V>> where Q: Hash + Equivalent<K> + ?Sized {
+        if !self.is_allocated() {
+            return None;
+        }
+
+        let hash = self.make_hash(key);
+        self.candidate_buckets(hash).flatten().find(|slot| slot.hash == hash && key.equivalent(&slot.key))
+    }
+
+    // Like `find`, but for `K` itself, and also counts how many keys were compared against `key`.
+    fn probe(&self, key: &K) -> (Option<&Slot<K, V>>, usize) {
+        if !self.is_allocated() {
+            return (None, 0);
+        }
+
+        let hash = self.make_hash(key);
+        let mut probes = 0;
+        for bucket in self.candidate_buckets(hash) {
+            let (position, count) = self.search(bucket, hash, key);
+            probes += count;
+            if let Some(position) = position {
+                return (Some(&bucket[position]), probes);
+            }
+        }
+        (None, probes)
+    }
+
+    // Finds the position of `key` in `bucket`, binary searching the bucket if it's treeified,
+    // along with how many keys were compared against it.
+    fn search(&self, bucket: &[Slot<K, V>], hash: u64, key: &K) -> (Option<usize>, usize) {
+        let mut probes = 0;
+        let position = match self.treeified(bucket.len()) {
+            Some(compare) => bucket.binary_search_by(|slot| {
+                probes += 1;
+                compare(&slot.key, key)
+            }).ok(),
+            None => bucket.iter().position(|slot| {
+                probes += 1;
+                // Most keys in a bucket have different hashes, so this usually saves comparing them
+                slot.hash == hash && &slot.key == key
+            })
+        };
+        (position, probes)
+    }
+
+    // Grows the map, following its growth factor, if it has dynamic resizing enabled but holds
+    // more entries than its load factor allows.
+    fn grow_to_threshold(&mut self) {
+        if self.options.dynamic_resizing() && self.exceeds_threshold(self.size) {
+            self.resize(self.capacity_for(self.size));
+        }
+    }
+
+    // Takes the value out of the slot at a position in a bucket and replaces it with the result
+    // of `f`, or removes the slot if `f` returns nothing. The slot is out of the map while `f`
+    // runs, so a panic in `f` only loses that entry, and a surviving entry goes back to the same
+    // position so neither the bucket's order nor `iter_stable`'s changes.
+    fn compute_at<F>(&mut self, index: usize, position: usize, f: F) -> Option<&mut V> where F: FnOnce(&K, V) -> Option<V> {
+        // Shifting rather than swapping keeps the rest of the bucket in order, treeified or not
+        let slot = self.items[index].remove(position);
+        self.size -= 1;
+        match f(&slot.key, slot.value) {
+            Some(value) => {
+                self.size += 1;
+                self.items[index].insert(position, Slot { value, ..slot });
+                Some(&mut self.items[index][position].value)
+            },
+            None => {
+                self.shrink_if_sparse();
+                None
+            }
+        }
+    }
+
+    // Adds an entry for a key which isn't in the map yet, first growing the map if the new entry
+    // would put it over its load factor. The key's hash comes from looking it up, so it isn't
+    // computed twice. Returns a reference to the newly inserted value.
+    fn insert_new_hashed(&mut self, hash: u64, key: K, value: V) -> &mut V {
+        if self.options.dynamic_resizing() && self.exceeds_threshold(self.size + 1) {
+            let capacity = (self.capacity() as f64 * self.options.growth_factor()).ceil() as usize;
+            if self.options.incremental_resizing() {
+                self.start_migration(capacity);
+            } else {
+                self.resize(capacity);
+            }
+        }
+
+        self.insert_no_resize_hashed(hash, key, value)
+    }
+
+    // Like `insert_new_hashed`, but without checking the load factor.
+    fn insert_no_resize_hashed(&mut self, hash: u64, key: K, value: V) -> &mut V {
+        if !self.is_allocated() {
+            self.items = Self::create_backing_vec(self.capacity());
+        }
+
+        let order = self.next_order;
+        if self.options.insertion_ordered() {
+            self.next_order += 1;
+        }
+
+        let index = self.index_for_hash(hash, self.capacity());
+        self.size += 1;
+        &mut self.push_slot(index, Slot { key, value, hash, order }).value
+    }
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V> {
+    /// Creates a new [HashMap] with the default options, pairing up each key with the value at
+    /// the same position. If one iterator is longer than the other, its extra items are ignored.
+    /// Like [HashMap::put], later duplicate keys overwrite earlier ones.
+    pub fn from_keys_values(keys: impl IntoIterator<Item = K>, values: impl IntoIterator<Item = V>) -> Self {
+        let mut map = HashMap::new();
+        for (key, value) in keys.into_iter().zip(values) {
+            map.put(key, value);
+        }
+        map
+    }
+
+    /// Creates a new [HashMap] with the default options from a sequence of entries. Later entries
+    /// overwrite earlier ones with the same key.
+    pub fn from_entries(entries: impl IntoIterator<Item = Entry<K, V>>) -> Self {
+        let mut map = HashMap::new();
+        for entry in entries {
+            map.put(entry.key, entry.value);
+        }
+        map
+    }
+}
+
+impl<K: Hash + Eq> HashMap<K, usize> {
+    /// Creates a new [HashMap] with the default options, counting how many times each item
+    /// appears in `items`.
+    pub fn frequencies<I: IntoIterator<Item = K>>(items: I) -> Self {
+        let mut map = HashMap::new();
+        for item in items {
+            map.accumulate(item, || 0, |count| *count += 1);
+        }
+        map
+    }
+}
+
+impl<K: Hash + Eq, T> HashMap<K, Vec<T>> {
+    /// Creates a new [HashMap] with the default options, grouping `items` by the key `key_fn`
+    /// computes for each of them. Each group keeps its items in the order they appeared in.
+    pub fn group_by<I, F>(items: I, mut key_fn: F) -> Self where I: IntoIterator<Item = T>, F: FnMut(&T) -> K {
+        let mut map = HashMap::new();
+        for item in items {
+            map.entry(key_fn(&item)).or_insert_with(Vec::new).push(item);
+        }
+        map
+    }
+}
+
+impl<K: Hash + Ord, V> HashMap<K, V> {
+    /// Creates a new [HashMap] with the given options, making use of the keys' ordering.
+    /// Unlike [HashMap::with_options], maps created this way honour the `treeify_threshold`
+    /// option: any bucket holding more entries than the threshold is kept sorted by key so it can
+    /// be binary searched, and goes back to being searched linearly once it shrinks again.
+    /// Lookups through a borrowed form of the key, like [HashMap::get] and [HashMap::pop], can't
+    /// make use of `K`'s ordering and still scan these buckets linearly.
+    pub fn with_options_ord(options: Options) -> Self {
+        HashMap { compare: Some(K::cmp), ..HashMap::with_options(options) }
+    }
+}
+
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
+    fn default() -> Self {
+        HashMap::with_hasher(S::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for HashMap<K, V, S> {
+    /// Formats the map like `{"key": value, ...}`, in the same order as [HashMap::iter].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Entry<K, V> {
+    /// Formats the entry like a map holding only it, i.e. `{"key": value}`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entry(&self.key, &self.value).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for HashMap<K, V, S> {
+    /// Maps are equal when they hold the same `(key, value)` pairs. See [HashMap::structural_eq].
+    fn eq(&self, other: &Self) -> bool {
+        self.structural_eq(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for HashMap<K, V, S> {}
+
+impl<K, Q, V, S> Index<&Q> for HashMap<K, V, S> where K: Hash + Eq, Q: Hash + Equivalent<K> + ?Sized, S: BuildHasher {
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to `key`.
+    ///
+    /// Panics if the key is not in the map. See [HashMap::get] for a non-panicking version.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in HashMap")
+    }
+}
+
+impl<K: Hash + Eq, V, const N: usize> From<[(K, V); N]> for HashMap<K, V> {
+    /// Creates a new [HashMap] with the default options from an array of `(key, value)` pairs.
+    /// Later pairs overwrite earlier ones with the same key.
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMap<K, V, S> {
+    /// Creates a new [HashMap] with the default options from `(key, value)` pairs. Later pairs
+    /// overwrite earlier ones with the same key.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = HashMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashMap<K, V, S> {
+    /// Puts every `(key, value)` pair into the map. If the map has dynamic resizing enabled, it is
+    /// grown up front to fit the number of pairs the iterator is known to yield, rather than being
+    /// resized repeatedly along the way. If the map already has entries, only room for half of
+    /// them is reserved, since some of the keys may already be in the map and growing for them
+    /// would leave it oversized.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if self.options.dynamic_resizing() {
+            let (lower, _) = iter.size_hint();
+            self.reserve(if self.is_empty() { lower } else { lower.div_ceil(2) });
+        }
+
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for HashMap<K, V, S> {
+    /// Copies the map bucket by bucket, so no entries need to be rehashed.
+    fn clone(&self) -> Self {
+        HashMap {
+            items: self.items.clone(),
+            old_items: self.old_items.clone(),
+            migrated: self.migrated,
+            size: self.size,
+            next_order: self.next_order,
+            resize_count: self.resize_count,
+            options: self.options.clone(),
+            compare: self.compare,
+            hash_builder: self.hash_builder.clone()
+        }
+    }
+
+    /// Overwrites this map with a copy of `source`, reusing this map's existing allocations. When
+    /// both maps have the same capacity, neither the backing vector nor any bucket large enough to
+    /// hold its counterpart in `source` needs to reallocate.
+    fn clone_from(&mut self, source: &Self) {
+        self.items.clone_from(&source.items);
+        self.old_items.clone_from(&source.old_items);
+        self.migrated = source.migrated;
+        self.size = source.size;
+        self.next_order = source.next_order;
+        self.resize_count = source.resize_count;
+        self.options = source.options.clone();
+        self.compare = source.compare;
+        self.hash_builder.clone_from(&source.hash_builder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::DefaultHasher, hash::{BuildHasherDefault, Hasher}, iter::FusedIterator};
+
+    use crate::hashmap::hashers::CountingState;
+
+    use super::*;
+
+    #[test]
+    fn test_get_put() {
+        let mut map: HashMap<&str, &str> = HashMap::new();
+
+        // value is None when not present
+        assert_eq!(map.get(&"foo"), None);
+
+        // verify put and get
+        map.put("foo", "1");
+        assert_eq!(map.get(&"foo"), Some(&"1"));
+
+        // verify that another key/value pair works
+        map.put("bar", "2");
+        assert_eq!(map.get(&"bar"), Some(&"2"));
+    }
+
+    /// A deliberately simple FNV-1a hasher, to check maps only hash through their [BuildHasher]
+    #[derive(Default)]
+    struct FnvHasher(u64);
+
+    impl Hasher for FnvHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_hasher() {
+        let mut map = HashMap::with_hasher(BuildHasherDefault::<FnvHasher>::default());
+        let initial_capacity = map.capacity();
+
+        for i in 0..100 {
+            assert_eq!(map.put(i, i * 2), None);
+        }
+        assert!(map.capacity() > initial_capacity);
+
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(map.pop(&7), Some(14));
+        assert_eq!(map.get(&7), None);
+        assert!(map.validate_integrity().is_ok());
+
+        // Keys land in the bucket the map's own hasher picks
+        let bucket = map.find_key_index(&1, map.capacity());
+        assert!(map.items[bucket].iter().any(|slot| slot.key == 1));
+    }
+
+    #[test]
+    fn test_random_seeding() {
+        let a: HashMap<i32, i32> = HashMap::new();
+        let b: HashMap<i32, i32> = HashMap::new();
+
+        // Each map gets its own seed, but clones keep theirs
+        assert_ne!(a.hasher().hash_one(1), b.hasher().hash_one(1));
+        assert_eq!(a.hasher().hash_one(1), a.clone().hasher().hash_one(1));
+    }
+
+    #[test]
+    fn test_with_capacity_and_hasher() {
+        let fixed = BuildHasherDefault::<DefaultHasher>::default();
+        let mut a = HashMap::with_capacity_and_hasher(8, fixed.clone());
+        let mut b = HashMap::with_capacity_and_hasher(8, fixed);
+        assert_eq!(a.capacity(), 8);
+        assert!(HashMap::<i32, i32, _>::try_with_capacity_and_hasher(0, RandomState::new()).is_err());
+
+        // Opting out of random seeding puts the same keys in the same buckets in every map
+        for i in 0..6 {
+            a.put(i, i);
+            b.put(i, i);
+        }
+        for i in 0..6 {
+            assert_eq!(a.find_key_index(&i, 8), b.find_key_index(&i, 8));
+        }
+    }
+
+    #[test]
+    fn test_from_keys_values() {
+        let map = HashMap::from_keys_values(["a", "b", "c"], [1, 2, 3]);
+        assert_eq!(map.size(), 3);
+        assert_eq!(map.get(&"a"), Some(&1));
+        assert_eq!(map.get(&"b"), Some(&2));
+        assert_eq!(map.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn test_from_keys_values_mismatched() {
+        // Extra keys are dropped
+        let map = HashMap::from_keys_values(["a", "b", "c"], [1, 2]);
+        assert_eq!(map.size(), 2);
+        assert_eq!(map.get(&"a"), Some(&1));
+        assert_eq!(map.get(&"b"), Some(&2));
+        assert_eq!(map.get(&"c"), None);
+
+        // Extra values are dropped
+        let map = HashMap::from_keys_values(["a"], [1, 2, 3]);
+        assert_eq!(map.size(), 1);
+        assert_eq!(map.get(&"a"), Some(&1));
+    }
+
+    #[test]
+    fn test_from_entries() {
+        let entries = vec![
+            Entry::new("a", 1),
+            Entry::new("b", 2),
+            Entry::new("a", 3)
+        ];
+
+        let map = HashMap::from_entries(entries);
+        assert_eq!(map.size(), 2);
+        assert_eq!(map.get(&"a"), Some(&3));
+        assert_eq!(map.get(&"b"), Some(&2));
+
+        // Entries can be filtered on their way from one map to another
+        let map = HashMap::from_entries(map.into_iter()
+            .filter(|(_, value)| *value > 2)
+            .map(|(key, value)| Entry::new(key, value)));
+        assert_eq!(map.size(), 1);
+        assert_eq!(map.get(&"a"), Some(&3));
+    }
+
+    #[test]
+    fn test_try_insert() {
+        let mut map = HashMap::new();
+
+        assert_eq!(map.try_insert("foo", 1), Ok(&mut 1));
+        *map.try_insert("bar", 2).unwrap() += 1;
+        assert_eq!(map.get(&"bar"), Some(&3));
+
+        assert_eq!(map.try_insert("foo", 10), Err(("foo", 10)));
+        assert_eq!(map.get(&"foo"), Some(&1));
+        assert_eq!(map.size(), 2);
+    }
+
+    #[test]
+    fn test_insert_hashes_once() {
+        let mut map = HashMap::with_hasher(CountingState::default());
+
+        // Including the puts which grow the map
+        for i in 0..20 {
+            map.put(i, i);
+            assert_eq!(map.hasher().0.get(), i + 1);
+        }
+        assert!(map.resize_count() > 0);
+        map.try_insert(20, 20).unwrap();
+        map.get_or_insert_with(21, || 21);
+        map.compute(22, |_, _| Some(22));
+        assert_eq!(map.hasher().0.get(), 23);
+        assert_eq!(map.size(), 23);
+    }
+
+    #[test]
+    fn test_insert_if_absent() {
+        let mut map = HashMap::new();
+
+        assert!(map.insert_if_absent("foo", 1));
+        assert!(!map.insert_if_absent("foo", 2));
+        assert_eq!(map.get(&"foo"), Some(&1));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut map = HashMap::new();
+        map.put(String::from("foo"), 1);
+
+        assert_eq!(map.replace("foo", 2), Ok(1));
+        assert_eq!(map.get("foo"), Some(&2));
+
+        assert_eq!(map.replace("bar", 3), Err(3));
+        assert_eq!(map.get("bar"), None);
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_put_all() {
+        let mut map = HashMap::new();
+        map.put_all((0..1000).map(|i| (i, i * 2)));
+
+        assert_eq!(map.size(), 1000);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.get(&999), Some(&1998));
+        // Grown once rather than doubling from the default capacity every time it filled up
+        assert_eq!(map.resize_count(), 1);
+    }
+
+    #[test]
+    fn test_get_many() {
+        let map: HashMap<String, i32> = [("a", 1), ("b", 2)].iter()
+            .map(|(key, value)| (key.to_string(), *value))
+            .collect();
+
+        let values: Vec<Option<&i32>> = map.get_many(["b", "c", "a"]).collect();
+        assert_eq!(values, vec![Some(&2), None, Some(&1)]);
+    }
+
+    #[test]
+    fn test_put_no_resize() {
+        let mut map = HashMap::with_capacity(16);
+        map.resize(512);
+        assert_eq!(map.resize_count(), 1);
+
+        for i in 0..300 {
+            assert_eq!(map.put_no_resize(i, i), None);
+        }
+        assert_eq!(map.put_no_resize(0, 10), Some(0));
+
+        assert_eq!(map.resize_count(), 1);
+        assert_eq!(map.capacity(), 512);
+        assert_eq!(map.size(), 300);
+        for i in 1..300 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        assert_eq!(map.get(&0), Some(&10));
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut map: HashMap<String, i32> = HashMap::new();
+
+        map.put("foo".to_string(), 1);
+        map.put("bar".to_string(), 2);
+
+        assert_eq!(map.get("foo"), Some(&1));
+        assert_eq!(map.get(&"foo".to_string()), Some(&1));
+        assert_eq!(map.get("baz"), None);
+
+        assert_eq!(map.pop("foo"), Some(1));
+        assert_eq!(map.pop("foo"), None);
+        assert_eq!(map.get("bar"), Some(&2));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut map = HashMap::new();
+        assert_eq!(format!("{:?}", map), "{}");
+
+        map.put("foo", 1);
+        assert_eq!(format!("{:?}", map), r#"{"foo": 1}"#);
+
+        map.put("bar", 2);
+        let formatted = format!("{:?}", map);
+        assert!(formatted == r#"{"foo": 1, "bar": 2}"# || formatted == r#"{"bar": 2, "foo": 1}"#);
+    }
+
+    #[test]
+    fn test_debug_entry() {
+        let entry = Entry::new("foo", vec![1, 2]);
+        assert_eq!(format!("{:?}", entry), r#"{"foo": [1, 2]}"#);
+    }
+
+    #[test]
+    fn test_entry_accessors() {
+        let entry = Entry::new("foo", 1);
+        assert_eq!(entry.key(), &"foo");
+        assert_eq!(entry.value(), &1);
+        assert_eq!(entry.into_parts(), ("foo", 1));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut map = HashMap::new();
+        map.put(String::from("foo"), 1);
+        map.put(String::from("bar"), 2);
+
+        assert_eq!(map[&String::from("foo")], 1);
+        assert_eq!(map["bar"], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "key not found")]
+    fn test_index_missing_key() {
+        let map: HashMap<i32, i32> = HashMap::new();
+        let _ = map[&1];
+    }
+
+    #[test]
+    fn test_contains_key() {
+        let mut map: HashMap<String, i32> = HashMap::new();
+
+        assert!(!map.contains_key("foo"));
+
+        map.put("foo".to_string(), 1);
+        assert!(map.contains_key("foo"));
+        assert!(!map.contains_key("bar"));
+
+        map.pop("foo");
+        assert!(!map.contains_key("foo"));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut map = HashMap::new();
+
+        assert_eq!(map.get_mut(&"foo"), None);
+
+        map.put("foo", 1);
+        *map.get_mut(&"foo").unwrap() += 10;
+        assert_eq!(map.get(&"foo"), Some(&11));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_get_mut_colliding() {
+        let mut map = HashMap::new();
+
+        for i in 0..5 {
+            map.put(MyKey::new(i), i);
+        }
+
+        // Only the matching entry in the shared bucket changes
+        *map.get_mut(&MyKey::new(2)).unwrap() = 20;
+        for i in 0..5 {
+            assert_eq!(map.get(&MyKey::new(i)), Some(&if i == 2 { 20 } else { i }));
+        }
+        assert_eq!(map.get_mut(&MyKey::new(5)), None);
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut map = HashMap::new();
+
+        map.put("foo", "1");
+        assert_eq!(map.pop(&"foo"), Some("1"));
+        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.pop(&"foo"), None);
+    }
+
+    #[test]
+    fn test_get_key_value() {
+        let mut map = HashMap::new();
+        let key = String::from("foo");
+        let stored = key.as_ptr();
+        map.put(key, 1);
+
+        let (key, value) = map.get_key_value("foo").unwrap();
+        assert_eq!(key.as_ptr(), stored);
+        assert_eq!(value, &1);
+        assert_eq!(map.get_key_value("bar"), None);
+    }
+
+    #[test]
+    fn test_remove_entry() {
+        let mut map = HashMap::new();
+        let key = String::from("foo");
+        let stored = key.as_ptr();
+        map.put(key, 1);
+
+        let (key, value) = map.remove_entry("foo").unwrap();
+        assert_eq!(key.as_ptr(), stored);
+        assert_eq!(value, 1);
+        assert_eq!(map.size(), 0);
+        assert_eq!(map.remove_entry("foo"), None);
+    }
+
+    #[test]
+    fn test_pop_any() {
+        let mut map: HashMap<i32, i32> = (0..50).map(|i| (i, i * 10)).collect();
+
+        let mut popped = Vec::new();
+        while let Some((key, value)) = map.pop_any() {
+            assert_eq!(value, key * 10);
+            popped.push(key);
+        }
+        popped.sort();
+
+        assert_eq!(popped, (0..50).collect::<Vec<_>>());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_sample() {
+        let map: HashMap<i32, i32> = (0..50).map(|i| (i, i * 10)).collect();
+
+        // Counting up through every possible pick visits every entry exactly once
+        let mut next = 0;
+        let mut rng = || {
+            next += 1;
+            next - 1
+        };
+        let mut sampled: Vec<i32> = (0..50).map(|_| *map.sample(&mut rng).unwrap().0).collect();
+        sampled.sort();
+        assert_eq!(sampled, (0..50).collect::<Vec<_>>());
+
+        let empty: HashMap<i32, i32> = HashMap::new();
+        assert_eq!(empty.sample(&mut || 0), None);
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let mut map = HashMap::new();
+
+        map.put("foo", 1);
+        map.put("bar", 2);
+        map.put("baz", 3);
+
+        assert_eq!(map.remove_all(&["foo", "qux", "bar"]), 2);
+        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.get(&"bar"), None);
+        assert_eq!(map.get(&"baz"), Some(&3));
+        assert_eq!(map.size(), 1);
+
+        // Duplicates only count once
+        assert_eq!(map.remove_all(&["baz", "baz"]), 1);
+        assert_eq!(map.size(), 0);
+
+        assert_eq!(map.remove_all(&["foo"]), 0);
+    }
+
+    #[test]
+    fn test_put_overwrite() {
+        let mut map = HashMap::new();
+
+        map.put("foo", "1");
+        assert_eq!(map.put("foo", "2"), Some("1"));
+        assert_eq!(map.get(&"foo"), Some(&"2"));
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    struct MyKey {
+        foo: i32
+    }
+
+    impl MyKey {
+        pub fn new(val: i32) -> Self { MyKey { foo: val } }
+    }
+
+    impl Hash for MyKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            state.write_i32(1); // Always give same hash
+        }
+    }
+
+    #[test]
+    fn test_keys_colliding_hash () {
+        let mut map = HashMap::new();
+
+        // Sanity check that hashes are the same
+        assert_eq!(map.hasher().hash_one(MyKey::new(1)), map.hasher().hash_one(MyKey::new(2)));
+
+        // Insert two different K->V pairs with same hash
+        assert_eq!(map.put(MyKey::new(1), "1"), None);
+        assert_eq!(map.put(MyKey::new(2), "2"), None);
+
+        assert_eq!(map.get(&MyKey::new(1)), Some(&"1"));
+        assert_eq!(map.get(&MyKey::new(2)), Some(&"2"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut mine = HashMap::frequencies(["a", "b", "a"]);
+        let theirs = HashMap::frequencies(["a", "c"]);
+
+        let mut conflicts = Vec::new();
+        mine.merge(theirs, |key, mine, theirs| {
+            conflicts.push(*key);
+            *mine += theirs;
+        });
+
+        assert_eq!(conflicts, vec!["a"]);
+        assert_eq!(mine.size(), 3);
+        assert_eq!(mine.get(&"a"), Some(&3));
+        assert_eq!(mine.get(&"b"), Some(&1));
+        assert_eq!(mine.get(&"c"), Some(&1));
+    }
+
+    #[test]
+    fn test_merge_keep_newest() {
+        let mut mine: HashMap<i32, &str> = (0..10).map(|i| (i, "old")).collect();
+        let theirs: HashMap<i32, &str> = (5..15).map(|i| (i, "new")).collect();
+
+        mine.merge(theirs, |_, mine, theirs| *mine = theirs);
+
+        assert_eq!(mine.size(), 15);
+        assert_eq!(mine.get(&0), Some(&"old"));
+        assert_eq!(mine.get(&5), Some(&"new"));
+        assert_eq!(mine.get(&14), Some(&"new"));
+    }
+
+    #[test]
+    fn test_replace_key() {
+        let mut map = HashMap::new();
+        let capacity = map.capacity();
+
+        // Find a new key that lands in a different bucket than the old one
+        let old = 1;
+        let new = (2..).find(|key| map.find_key_index(key, capacity) != map.find_key_index(&old, capacity)).unwrap();
+
+        map.put(old, "1");
+        assert_eq!(map.replace_key(&old, new), Ok(()));
+        assert_eq!(map.get(&old), None);
+        assert_eq!(map.get(&new), Some(&"1"));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_replace_key_same_bucket() {
+        let mut map = HashMap::new();
+
+        map.put(MyKey::new(1), "1");
+        map.put(MyKey::new(2), "2");
+        assert_eq!(map.replace_key(&MyKey::new(1), MyKey::new(3)), Ok(()));
+        assert_eq!(map.get(&MyKey::new(1)), None);
+        assert_eq!(map.get(&MyKey::new(2)), Some(&"2"));
+        assert_eq!(map.get(&MyKey::new(3)), Some(&"1"));
+    }
+
+    #[test]
+    fn test_replace_key_errors() {
+        let mut map = HashMap::new();
+
+        map.put("foo", "1");
+        map.put("bar", "2");
+
+        assert_eq!(map.replace_key(&"baz", "qux"), Err(ReplaceKeyError::MissingKey));
+        assert_eq!(map.replace_key(&"foo", "bar"), Err(ReplaceKeyError::KeyExists));
+
+        // Failed replacements leave the map untouched
+        assert_eq!(map.get(&"foo"), Some(&"1"));
+        assert_eq!(map.get(&"bar"), Some(&"2"));
+        assert_eq!(map.size(), 2);
+    }
+
+    fn map_with_capacity<K: Hash + Eq, V>(capacity: usize) -> HashMap<K, V> {
+        HashMap::with_options(Options::builder().initial_capacity(capacity).build().unwrap())
+    }
+
+    #[test]
+    fn test_structural_eq() {
+        let mut a = map_with_capacity(2);
+        let mut b = map_with_capacity(64);
+        assert!(a.structural_eq(&b));
+
+        a.put("foo", 1);
+        a.put("bar", 2);
+        b.put("bar", 2);
+        assert!(!a.structural_eq(&b));
+        assert!(!b.structural_eq(&a));
+
+        b.put("foo", 1);
+        assert!(a.structural_eq(&b));
+
+        b.put("foo", 3);
+        assert!(!a.structural_eq(&b));
+    }
+
+    // Lets nested maps be compared as values by their logical contents
+    struct Inner(HashMap<&'static str, i32>);
+
+    impl PartialEq for Inner {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.structural_eq(&other.0)
+        }
+    }
+
+    fn make_inner(capacity: usize, value: i32) -> Inner {
+        let mut inner = map_with_capacity(capacity);
+        inner.put("inner", value);
+        Inner(inner)
+    }
+
+    #[test]
+    fn test_partial_eq() {
+        let expected: HashMap<i32, i32> = (0..20).map(|i| (i, i)).collect();
+        let mut actual = map_with_capacity(4);
+        for i in (0..20).rev() {
+            actual.put(i, i);
+        }
+        assert_eq!(expected, actual);
+
+        actual.put(0, 100);
+        assert_ne!(expected, actual);
+        actual.put(0, 0);
+        actual.put(20, 20);
+        assert_ne!(expected, actual);
+    }
+
+    #[test]
+    fn test_structural_eq_nested() {
+        let mut a = map_with_capacity(2);
+        let mut b = map_with_capacity(32);
+
+        a.put("foo", make_inner(2, 1));
+        a.put("bar", make_inner(4, 2));
+        b.put("bar", make_inner(16, 2));
+        b.put("foo", make_inner(8, 1));
+        assert!(a.structural_eq(&b));
+
+        b.put("foo", make_inner(8, 5));
+        assert!(!a.structural_eq(&b));
+    }
+
+    #[test]
+    fn test_join() {
+        let names = HashMap::from([(1, "one"), (2, "two"), (3, "three")]);
+        let squares = HashMap::from([(2, 4), (3, 9), (4, 16)]);
+
+        let mut joined: Vec<(&i32, &&str, &i32)> = names.join(&squares).collect();
+        joined.sort();
+        assert_eq!(joined, vec![(&2, &"two", &4), (&3, &"three", &9)]);
+    }
+
+    #[test]
+    fn test_outer_join() {
+        let names = HashMap::from([(1, "one"), (2, "two")]);
+        let squares = HashMap::from([(2, 4), (3, 9)]);
+
+        let mut joined: Vec<(&i32, Option<&&str>, Option<&i32>)> = names.outer_join(&squares).collect();
+        joined.sort();
+        assert_eq!(joined, vec![
+            (&1, Some(&"one"), None),
+            (&2, Some(&"two"), Some(&4)),
+            (&3, None, Some(&9))
+        ]);
+    }
+
+    #[test]
+    fn test_difference_and_intersection_keys() {
+        let desired = HashMap::from([("a", 1), ("b", 2), ("c", 3)]);
+        let actual = HashMap::from([("b", "running"), ("c", "stopped"), ("d", "running")]);
+
+        let mut missing: Vec<(&&str, &i32)> = desired.difference_keys(&actual).collect();
+        missing.sort();
+        assert_eq!(missing, vec![(&"a", &1)]);
+
+        let mut present: Vec<(&&str, &i32)> = desired.intersection_keys(&actual).collect();
+        present.sort();
+        assert_eq!(present, vec![(&"b", &2), (&"c", &3)]);
+
+        let extra: Vec<&&str> = actual.difference_keys(&desired).map(|(key, _)| key).collect();
+        assert_eq!(extra, vec![&"d"]);
+    }
+
+    #[test]
+    fn test_keys_for_value() {
+        let mut map = HashMap::new();
+
+        map.put("foo", 1);
+        map.put("bar", 2);
+        map.put("baz", 1);
+        map.put("qux", 1);
+
+        let mut keys = map.keys_for_value(&1);
+        keys.sort();
+        assert_eq!(keys, vec![&"baz", &"foo", &"qux"]);
+
+        assert_eq!(map.keys_for_value(&2), vec![&"bar"]);
+        assert!(map.keys_for_value(&3).is_empty());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut map = HashMap::new();
+
+        for i in (0..20).rev() {
+            map.put(i, i * 10);
+        }
+
+        assert_eq!(map.range(3..6), vec![(&3, &30), (&4, &40), (&5, &50)]);
+        assert_eq!(map.range(3..=5), vec![(&3, &30), (&4, &40), (&5, &50)]);
+        assert_eq!(map.range(17..), vec![(&17, &170), (&18, &180), (&19, &190)]);
+        assert_eq!(map.range(..2), vec![(&0, &0), (&1, &10)]);
+
+        let all: Vec<(&i32, &i32)> = map.range(..);
+        assert_eq!(all.len(), 20);
+        assert!(all.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        assert!(map.range(5..5).is_empty());
+        assert!(map.range(30..40).is_empty());
+    }
+
+    #[test]
+    fn test_keys_collide() {
+        let mut map: HashMap<i32, i32> = HashMap::with_capacity(2);
+
+        // Find a pair of keys sharing a bucket now, but not once the map is larger
+        let a = 0;
+        let b = (1..)
+            .find(|b| map.find_key_index(&a, 2) == map.find_key_index(b, 2) && map.find_key_index(&a, 64) != map.find_key_index(b, 64))
+            .unwrap();
+
+        assert!(map.keys_collide(&a, &b));
+        assert!(map.keys_collide(&a, &a));
+
+        map.resize(64);
+        assert!(!map.keys_collide(&a, &b));
+        assert!(map.keys_collide(&a, &a));
+    }
+
+    #[test]
+    fn test_iter_stable() {
+        let mut map = HashMap::new();
+
+        for i in 1..6 {
+            map.put(MyKey::new(i), i);
+        }
+
+        // All keys share a bucket, so they come out in the order they were inserted
+        let values: Vec<i32> = map.iter_stable().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(map.remove_stable(&MyKey::new(2)), Some(2));
+        assert_eq!(map.remove_stable(&MyKey::new(2)), None);
+        assert_eq!(map.size(), 4);
+
+        let values: Vec<i32> = map.iter_stable().map(|(_, value)| *value).collect();
+        assert_eq!(values, vec![1, 3, 4, 5]);
+    }
+
+    fn treeified_map(threshold: usize) -> HashMap<MyKey, i32> {
+        HashMap::with_options_ord(
+            Options::builder().treeify_threshold(threshold).build().unwrap())
+    }
+
+    fn is_sorted(bucket: &[Slot<MyKey, i32>]) -> bool {
+        bucket.windows(2).all(|pair| pair[0].key < pair[1].key)
+    }
+
+    #[test]
+    fn test_treeify() {
+        let mut map = treeified_map(4);
+        let bucket = map.find_key_index(&MyKey::new(0), map.capacity());
+
+        // Below the threshold, entries are kept in insertion order
+        for i in (0..4).rev() {
+            map.put(MyKey::new(i), i);
+        }
+        assert!(!is_sorted(&map.items[bucket]));
+
+        // Past it, the bucket is sorted and stays sorted, including across resizes
+        for i in (4..50).rev() {
+            map.put(MyKey::new(i), i);
+        }
+        let bucket = map.find_key_index(&MyKey::new(0), map.capacity());
+        assert_eq!(map.items[bucket].len(), 50);
+        assert!(is_sorted(&map.items[bucket]));
+
+        for i in 0..50 {
+            assert_eq!(map.get(&MyKey::new(i)), Some(&i));
+        }
+        assert_eq!(map.get(&MyKey::new(50)), None);
+        assert_eq!(map.put(MyKey::new(10), 100), Some(10));
+        assert_eq!(map.get(&MyKey::new(10)), Some(&100));
+    }
+
+    #[test]
+    fn test_treeify_pop() {
+        let mut map = treeified_map(4);
+
+        for i in 0..10 {
+            map.put(MyKey::new(i), i);
+        }
+
+        let bucket = map.find_key_index(&MyKey::new(0), map.capacity());
+        for i in (0..10).step_by(2) {
+            assert_eq!(map.pop(&MyKey::new(i)), Some(i));
+            assert!(map.items[bucket].len() <= 4 || is_sorted(&map.items[bucket]));
+        }
+
+        // Shrinking back under the threshold keeps lookups working
+        for i in 0..10 {
+            assert_eq!(map.get(&MyKey::new(i)), if i % 2 == 0 { None } else { Some(&i) });
+        }
+        assert_eq!(map.size(), 5);
+    }
+
+    #[test]
+    fn test_treeify_ignored_without_ord() {
+        let mut map = HashMap::with_options(
+            Options::builder().treeify_threshold(2).build().unwrap());
+
+        for i in (0..10).rev() {
+            map.put(MyKey::new(i), i);
+        }
+
+        let bucket = map.find_key_index(&MyKey::new(0), map.capacity());
+        assert!(!is_sorted(&map.items[bucket]));
+        assert_eq!(map.get(&MyKey::new(3)), Some(&3));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut map = HashMap::with_options(
+            Options::builder().initial_capacity(16).dynamic_resizing(false).build().unwrap()
+        );
+
+        let entries: Vec<(String, i32)> = (1..100).map(|i| i.to_string()).zip(1..100).collect();
+
+        // Fill map
+        for entry in entries.iter() {
+            map.put(&entry.0[..], entry.1);
+        }
+
+        // Resizing map larger doesn't mess up keys
+        map.resize(100);
+        for entry in entries.iter() {
+            assert_eq!(map.get(&&entry.0[..]), Some(&entry.1))
+        }
+
+        // Shrinking map doesn't mess up keys
+        map.resize(2);
+        for entry in entries.iter() {
+            assert_eq!(map.get(&&entry.0[..]), Some(&entry.1))
+        }
+    }
+
+    #[test]
+    fn test_trim_empty_buckets() {
+        let mut map = HashMap::with_capacity(8);
+        for i in 0..5 {
+            map.put(i, i);
+        }
+
+        // Empty out the bucket holding key 0, keeping everything else
+        let emptied = map.find_key_index(&0, map.capacity());
+        for i in 0..5 {
+            if map.find_key_index(&i, map.capacity()) == emptied {
+                map.pop(&i);
+            }
+        }
+        assert!(map.items[emptied].capacity() > 0);
+
+        let capacities: Vec<usize> = map.items.iter().map(|bucket| bucket.capacity()).collect();
+        map.trim_empty_buckets();
+
+        for (bucket, capacity) in map.items.iter().zip(capacities) {
+            if bucket.is_empty() {
+                assert_eq!(bucket.capacity(), 0);
+            } else {
+                assert_eq!(bucket.capacity(), capacity);
+            }
+        }
+        assert_eq!(map.capacity(), 8);
+        assert_eq!(map.validate_integrity(), Ok(()));
+    }
+
+    #[test]
+    fn test_trim_empty_buckets_while_migrating() {
+        let options = Options::builder().initial_capacity(16).incremental_resizing(true).build().unwrap();
+        let mut map = HashMap::with_options(options);
+        for i in 0..11 {
+            map.put(i, i);
+        }
+
+        // Empty out a bucket before growing, so it's still in the old table afterwards
+        let emptied = map.find_key_index(&0, map.capacity());
+        for i in 0..11 {
+            if map.find_key_index(&i, map.capacity()) == emptied {
+                map.pop(&i);
+            }
+        }
+        let mut next = 11;
+        while !map.is_migrating() {
+            if map.find_key_index(&next, map.capacity()) != emptied {
+                map.put(next, next);
+            }
+            next += 1;
+        }
+        assert!(map.old_items[emptied].is_empty());
+        assert!(map.old_items[emptied].capacity() > 0);
+
+        map.trim_empty_buckets();
+        assert_eq!(map.old_items[emptied].capacity(), 0);
+        assert_eq!(map.validate_integrity(), Ok(()));
+    }
+
+    #[test]
+    fn test_shard() {
+        let mut map = HashMap::new();
+
+        let entries: Vec<(i32, i32)> = (1..100).zip(1..100).collect();
+        for entry in entries.iter() {
+            map.put(entry.0, entry.1);
+        }
+
+        let size = map.size();
+        let shards = map.shard(4);
+        assert_eq!(shards.len(), 4);
+        assert_eq!(shards.iter().map(|shard| shard.size()).sum::<usize>(), size);
+
+        // Every key lives in the shard its hash points to
+        for entry in entries.iter() {
+            let shard = &shards[shards[0].find_key_index(&entry.0, 4)];
+            assert_eq!(shard.get(&entry.0), Some(&entry.1));
+        }
+    }
+
+    #[test]
+    fn test_shard_reuses_hashes() {
+        let mut map = HashMap::with_hasher(CountingState::default());
+        for i in 0..100 {
+            map.put(i, i);
+        }
+        assert_eq!(map.hasher().0.get(), 100);
+
+        // Each shard starts from a clone of the map's hasher, and never needs to use it
+        let shards = map.shard(4);
+        assert!(shards.iter().all(|shard| shard.hasher().0.get() == 100));
+        assert_eq!(shards.iter().map(|shard| shard.size()).sum::<usize>(), 100);
+    }
+
+    #[test]
+    fn test_resize_reusing() {
+        // A fixed hasher, so no bucket ends up with more entries than its allocation can hold
+        let mut map = HashMap::with_options_and_hasher(
+            Options::builder().initial_capacity(16).dynamic_resizing(false).build().unwrap(),
+            BuildHasherDefault::<DefaultHasher>::default()
+        );
+
+        let entries: Vec<(i32, i32)> = (1..100).zip(1..100).collect();
+        for entry in entries.iter() {
+            map.put(entry.0, entry.1);
+        }
+
+        let old_buckets: Vec<*const Slot<i32, i32>> = map.items.iter()
+            .filter(|bucket| bucket.capacity() > 0)
+            .map(|bucket| bucket.as_ptr())
+            .collect();
+
+        map.resize_reusing(100);
+        assert_eq!(map.capacity(), 100);
+        for entry in entries.iter() {
+            assert_eq!(map.get(&entry.0), Some(&entry.1))
+        }
+
+        // Every bucket allocated before growing is still in use afterwards
+        let new_buckets: Vec<*const Slot<i32, i32>> = map.items.iter()
+            .map(|bucket| bucket.as_ptr())
+            .collect();
+        assert!(old_buckets.iter().all(|bucket| new_buckets.contains(bucket)));
+
+        // Shrinking doesn't mess up keys either
+        map.resize_reusing(2);
+        assert_eq!(map.capacity(), 2);
+        for entry in entries.iter() {
+            assert_eq!(map.get(&entry.0), Some(&entry.1))
+        }
+    }
+
+    #[test]
+    fn test_transform_keys() {
+        let mut map = HashMap::new();
+
+        for i in 1..50 {
+            map.put(i, i.to_string());
+        }
+
+        let map = map.transform_keys(|key| key * 2);
+        assert_eq!(map.size(), 49);
+        for i in 1..50 {
+            assert_eq!(map.get(&(i * 2)), Some(&i.to_string()));
+        }
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[test]
+    fn test_map_values() {
+        let mut map = map_with_capacity(64);
+        for i in 1..40 {
+            map.put(i, i);
+        }
+
+        let map = map.map_values(|value| value.to_string());
+        assert_eq!(map.size(), 39);
+        assert_eq!(map.capacity(), 64);
+        for i in 1..40 {
+            assert_eq!(map.get(&i), Some(&i.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let map: HashMap<i32, i32> = (1..50).map(|i| (i, i * 10)).collect();
+
+        let map = map.filter(|key, value| key % 2 == 0 && *value > 100);
+        assert_eq!(map.size(), 19);
+        assert_eq!(map.get(&12), Some(&120));
+        assert_eq!(map.get(&10), None);
+        assert_eq!(map.get(&13), None);
+    }
+
+    #[test]
+    fn test_filter_map() {
+        let map: HashMap<i32, &str> = [(1, "1"), (2, "two"), (3, "3")].into_iter().collect();
+
+        let map = map.filter_map(|_, value| value.parse::<i32>().ok());
+        assert_eq!(map.size(), 2);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.get(&2), None);
+        assert_eq!(map.get(&3), Some(&3));
+    }
+
+    #[test]
+    fn test_filter_map_while_migrating() {
+        let options = Options::builder().initial_capacity(16).incremental_resizing(true).build().unwrap();
+        let mut map = HashMap::with_options(options);
+        for i in 0..12 {
+            map.put(i, i);
+        }
+        assert!(map.is_migrating());
+
+        let map = map.filter_map(|key, value| (key % 2 == 0).then_some(value * 10));
+        assert!(map.is_migrating());
+        assert_eq!(map.resize_count(), 1);
+        assert_eq!(map.size(), 6);
+        assert_eq!(map.validate_integrity(), Ok(()));
+        assert_eq!(map.get(&4), Some(&40));
+        assert_eq!(map.get(&5), None);
+    }
+
+    #[test]
+    fn test_invert() {
+        let map: HashMap<i32, String> = (1..50).map(|i| (i, i.to_string())).collect();
+
+        let inverted = map.invert();
+        assert_eq!(inverted.size(), 49);
+        for i in 1..50 {
+            assert_eq!(inverted.get(&i.to_string()), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_invert_duplicate_values() {
+        let map: HashMap<&str, i32> = [("a", 1), ("b", 2), ("c", 1)].into_iter().collect();
+
+        let inverted = map.invert();
+        assert_eq!(inverted.size(), 2);
+        assert!(matches!(inverted.get(&1), Some(&"a") | Some(&"c")));
+        assert_eq!(inverted.get(&2), Some(&"b"));
+    }
+
+    #[test]
+    fn test_invert_grouped() {
+        let map: HashMap<&str, i32> = [("a", 1), ("b", 2), ("c", 1)].into_iter().collect();
+
+        let mut inverted = map.invert_grouped();
+        assert_eq!(inverted.size(), 2);
+        inverted.get_mut(&1).unwrap().sort();
+        assert_eq!(inverted.get(&1), Some(&vec!["a", "c"]));
+        assert_eq!(inverted.get(&2), Some(&vec!["b"]));
+    }
+
+    #[test]
+    fn test_transform_keys_collision() {
+        let mut map = HashMap::new();
+
+        map.put(1, "odd");
+        map.put(2, "even");
+
+        let map = map.transform_keys(|_| "same");
+        assert_eq!(map.size(), 1);
+        assert!(matches!(map.get(&"same"), Some(&"odd") | Some(&"even")));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut map = HashMap::new();
+
+        *map.get_or_insert_with("foo", || 1) += 10;
+        assert_eq!(map.get(&"foo"), Some(&11));
+
+        *map.get_or_insert_with("foo", || panic!("Value should already exist")) += 10;
+        assert_eq!(map.get(&"foo"), Some(&21));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_compute() {
+        let mut map = HashMap::new();
+
+        // Insert
+        assert_eq!(map.compute("foo", |_, value| Some(value.unwrap_or(0) + 1)), Some(&mut 1));
+        // Update
+        assert_eq!(map.compute("foo", |_, value| Some(value.unwrap_or(0) + 1)), Some(&mut 2));
+        assert_eq!(map.get(&"foo"), Some(&2));
+        // Remove
+        assert_eq!(map.compute("foo", |_, _| None), None);
+        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.size(), 0);
+        // Do nothing
+        assert_eq!(map.compute("foo", |_, _| None), None);
+        assert_eq!(map.size(), 0);
+    }
+
+    #[test]
+    fn test_compute_if_present() {
+        let mut map = HashMap::new();
+        map.put(String::from("foo"), 1);
+
+        assert_eq!(map.compute_if_present("bar", |_, _| panic!("Key should be absent")), None);
+        assert_eq!(map.compute_if_present("foo", |key, value| Some(value + key.len())), Some(&mut 4));
+        assert_eq!(map.compute_if_present("foo", |_, _| None), None);
+        assert_eq!(map.size(), 0);
+    }
+
+    #[test]
+    fn test_compute_panic() {
+        let mut map = HashMap::with_capacity(4);
+        for i in 0..20 {
+            map.put(i, i);
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.compute(7, |_, _| panic!("Closure panicked"));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(map.validate_integrity(), Ok(()));
+        assert_eq!(map.size(), 19);
+        assert_eq!(map.iter().count(), 19);
+        assert_eq!(map.get(&7), None);
+        assert_eq!(map.get(&8), Some(&8));
+    }
+
+    #[test]
+    fn test_compute_keeps_stable_order() {
+        let options = Options::builder().initial_capacity(4).dynamic_resizing(false).build().unwrap();
+        let mut map = HashMap::with_options(options);
+        for i in 0..20 {
+            map.put(i, i);
+        }
+        let before: Vec<i32> = map.iter_stable().map(|(&key, _)| key).collect();
+
+        for i in 0..20 {
+            map.compute(i, |_, value| value.map(|value| value + 1));
+        }
+
+        let after: Vec<i32> = map.iter_stable().map(|(&key, _)| key).collect();
+        assert_eq!(before, after);
+        assert!(map.iter().all(|(key, value)| *value == key + 1));
+    }
+
+    #[test]
+    fn test_compute_treeified() {
+        let mut map = treeified_map(2);
+        for i in 0..6 {
+            map.put(MyKey::new(i), i);
+        }
+
+        map.compute(MyKey::new(2), |_, value| value.map(|value| value * 10));
+        map.compute(MyKey::new(4), |_, _| None);
+
+        let bucket = map.find_key_index(&MyKey::new(0), map.capacity());
+        assert!(is_sorted(&map.items[bucket]));
+        assert_eq!(map.get(&MyKey::new(2)), Some(&20));
+        assert_eq!(map.get(&MyKey::new(4)), None);
+        assert_eq!(map.size(), 5);
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let mut map = HashMap::new();
+
+        for word in "the cat and the dog and the bird".split(' ') {
+            map.accumulate(word, || 0, |count| *count += 1);
+        }
 
-        containing_list.iter()
-            .find(|entry| &entry.key == key)
-            .map(|entry| &entry.value)
+        assert_eq!(map.get(&"the"), Some(&3));
+        assert_eq!(map.get(&"and"), Some(&2));
+        assert_eq!(map.get(&"cat"), Some(&1));
+        assert_eq!(map.get(&"fish"), None);
+        assert_eq!(map.size(), 5);
     }
 
-    /// Puts a `(key, value)` pair in the map. This will overwrite any existing value for the given
-    /// key. Returns the existing value if it exists.
-    pub fn put(&mut self, key: K, value: V) -> Option<V> {
-        let index = find_key_index(&key, self.capacity());
-        let containing_list = &mut self.items[index];
-
-        let existing_entry = containing_list.iter_mut()
-            .find(|entry| entry.key == key);
+    #[test]
+    fn test_resize_counted() {
+        let mut map = HashMap::with_options(
+            Options::builder()
+                .initial_capacity(1)
+                .load_factor(1.0)
+                .dynamic_resizing(false)
+                .build().unwrap()
+        );
 
-        let existing_value = match existing_entry {
-            Some(entry) => Some(mem::replace(&mut entry.value, value)),
-            None => {
-                let new_entry = Entry { key: key, value: value };
-                containing_list.push(new_entry);
-                self.size += 1;
-                None
+        // Grow by doubling, the same way dynamic resizing would
+        let n = 1000;
+        let mut total_moved = 0;
+        for i in 0..n {
+            map.put(i, i);
+            if map.size() >= map.capacity() {
+                let moved = map.resize_counted(map.capacity() * 2);
+                assert_eq!(moved, map.size());
+                total_moved += moved;
             }
-        };
-
-        if self.options.dynamic_resizing() && self.exceeds_threshold() {
-            self.resize(self.capacity() * 2);
         }
 
-        existing_value
+        // Each resize moves as many entries as all the previous ones combined, so the
+        // total work stays linear in the number of entries
+        assert!(total_moved < 2 * n);
+        assert_eq!(map.resize_counted(map.capacity()), n);
     }
 
-    /// Returns the value corresponding to a key, if it exists.
-    pub fn pop(&mut self, key: &K) -> Option<V> {
-        let index = find_key_index(&key, self.capacity());
-        let containing_list = &mut self.items[index];
+    #[test]
+    fn test_add_assign() {
+        let mut map = HashMap::new();
+
+        map.add_assign("foo", 5);
+        assert_eq!(map.get(&"foo"), Some(&5));
 
-        containing_list.iter()
-            .position(|entry| &entry.key == key)
-            .map(|position| {
-                self.size -= 1;
-                containing_list.swap_remove(position).value
-            })
-    }
+        map.add_assign("bar", 1);
+        map.add_assign("foo", 10);
+        map.add_assign("foo", -3);
+        assert_eq!(map.get(&"foo"), Some(&12));
+        assert_eq!(map.get(&"bar"), Some(&1));
 
-    /// Resize the hash map to have the number of buckets specified by `capacity`. This is an expensive
-    /// operation because it has to rehash every entry in the map. If the map has dynamic resizing
-    /// enabled, it will automatically resize to maintain the configured load factor.
-    pub fn resize(&mut self, capacity: usize) {
-        let mut new_vec: Vec<Vec<Entry<K, V>>> = HashMap::create_backing_vec(capacity);
-        for entry in mem::take(&mut self.items).into_iter().flatten() {
-            let index = find_key_index(&entry.key, new_vec.len());
-            new_vec[index].push(entry)
-        }
-        self.items = new_vec;
+        let mut map = HashMap::new();
+        map.add_assign("zero", 0.0);
+        assert_eq!(map.get(&"zero"), Some(&0.0));
     }
 
-    /// Returns the current number of entries in the hash map.
-    pub fn size(&self) -> usize {
-        self.size
+    #[test]
+    fn test_frequencies() {
+        let map = HashMap::frequencies("mississippi".chars());
+
+        assert_eq!(map.get(&'m'), Some(&1));
+        assert_eq!(map.get(&'i'), Some(&4));
+        assert_eq!(map.get(&'s'), Some(&4));
+        assert_eq!(map.get(&'p'), Some(&2));
+        assert_eq!(map.get(&'x'), None);
+        assert_eq!(map.size(), 4);
     }
 
-    fn capacity(&self) -> usize {
-        self.items.len()
+    #[test]
+    fn test_from_iter() {
+        let map: HashMap<i32, i32> = (0..100).map(|i| (i, i * 2)).collect();
+
+        assert_eq!(map.size(), 100);
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        // The map was sized for all 100 entries before any were inserted
+        assert_eq!(map.resize_count(), 1);
     }
 
-    fn exceeds_threshold(&self) -> bool {
-        self.size() as f64 >= (self.capacity() as f64) * self.options.load_factor()
+    #[test]
+    fn test_from_array() {
+        let map = HashMap::from([("a", 1), ("b", 2), ("a", 3)]);
+
+        assert_eq!(map.size(), 2);
+        assert_eq!(map.get(&"a"), Some(&3));
+        assert_eq!(map.get(&"b"), Some(&2));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_hashmap_macro() {
+        let map = crate::hashmap!{
+            "a" => 1,
+            "b" => 2,
+        };
+        assert_eq!(map, HashMap::from([("a", 1), ("b", 2)]));
+
+        let empty: HashMap<&str, i32> = crate::hashmap!{};
+        assert!(empty.is_empty());
+    }
 
     #[test]
-    fn test_get_put() {
-        let mut map: HashMap<&str, &str> = HashMap::new();
+    fn test_extend() {
+        let mut map = HashMap::new();
+        map.put(0, "zero");
+        map.extend(vec![(1, "one"), (2, "two"), (0, "nothing")]);
 
-        // value is None when not present
-        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.size(), 3);
+        assert_eq!(map.get(&0), Some(&"nothing"));
+        assert_eq!(map.get(&1), Some(&"one"));
+        assert_eq!(map.get(&2), Some(&"two"));
+    }
 
-        // verify put and get
-        map.put("foo", "1");
-        assert_eq!(map.get(&"foo"), Some(&"1"));
+    #[test]
+    fn test_extend_without_dynamic_resizing() {
+        let mut map = HashMap::with_options(
+            Options::builder().initial_capacity(4).dynamic_resizing(false).build().unwrap()
+        );
+        map.extend((0..20).map(|i| (i, i)));
 
-        // verify that another key/value pair works
-        map.put("bar", "2");
-        assert_eq!(map.get(&"bar"), Some(&"2"));
+        assert_eq!(map.size(), 20);
+        assert_eq!(map.capacity(), 4);
+        assert_eq!(map.resize_count(), 0);
     }
 
     #[test]
-    fn test_pop() {
+    fn test_group_by() {
+        let map = HashMap::group_by(["apple", "avocado", "banana", "blueberry", "cherry"], |word| {
+            word.chars().next().unwrap()
+        });
+
+        assert_eq!(map.size(), 3);
+        assert_eq!(map.get(&'a'), Some(&vec!["apple", "avocado"]));
+        assert_eq!(map.get(&'b'), Some(&vec!["banana", "blueberry"]));
+        assert_eq!(map.get(&'c'), Some(&vec!["cherry"]));
+    }
+
+    #[test]
+    fn test_clone() {
         let mut map = HashMap::new();
+        for i in 0..50 {
+            map.put(i, i.to_string());
+        }
 
-        map.put("foo", "1");
-        assert_eq!(map.pop(&"foo"), Some("1"));
-        assert_eq!(map.get(&"foo"), None);
-        assert_eq!(map.pop(&"foo"), None);
+        let mut clone = map.clone();
+        assert!(clone.structural_eq(&map));
+        assert_eq!(clone.capacity(), map.capacity());
+
+        // The clone is independent of the original
+        clone.put(0, "changed".to_string());
+        clone.pop(&1);
+        assert_eq!(map.get(&0), Some(&"0".to_string()));
+        assert_eq!(map.get(&1), Some(&"1".to_string()));
     }
 
     #[test]
-    fn test_put_overwrite() {
-        let mut map = HashMap::new();
+    fn test_clone_from_reuses_buckets() {
+        let mut source: HashMap<i32, i32> = (0..50).map(|i| (i, i)).collect();
+        let mut target = source.clone();
+        let buckets: Vec<*const Slot<i32, i32>> = target.items.iter().map(|bucket| bucket.as_ptr()).collect();
+
+        // Like a simulation loop, update the source and copy it over the target again
+        for step in 1..5 {
+            for (_, value) in source.iter_mut() {
+                *value += step;
+            }
+            target.clone_from(&source);
 
-        map.put("foo", "1");
-        assert_eq!(map.put("foo", "2"), Some("1"));
-        assert_eq!(map.get(&"foo"), Some(&"2"));
+            assert!(target.structural_eq(&source));
+            let reused: Vec<*const Slot<i32, i32>> = target.items.iter().map(|bucket| bucket.as_ptr()).collect();
+            assert_eq!(reused, buckets);
+        }
     }
 
-    #[derive(PartialEq, Eq)]
-    struct MyKey {
-        foo: i32
+    // Counts how many times any key sharing its counter has been hashed
+    #[derive(Clone)]
+    struct CountingKey {
+        val: i32,
+        hashes: std::rc::Rc<std::cell::Cell<usize>>
     }
 
-    impl MyKey {
-        pub fn new(val: i32) -> Self { MyKey { foo: val } }
+    impl PartialEq for CountingKey {
+        fn eq(&self, other: &Self) -> bool { self.val == other.val }
     }
 
-    impl Hash for MyKey {
+    impl Eq for CountingKey {}
+
+    impl Hash for CountingKey {
         fn hash<H: Hasher>(&self, state: &mut H) {
-            state.write_i32(1); // Always give same hash
+            self.hashes.set(self.hashes.get() + 1);
+            self.val.hash(state);
         }
     }
 
     #[test]
-    fn test_keys_colliding_hash () {
+    fn test_clone_does_not_rehash() {
+        let hashes = std::rc::Rc::new(std::cell::Cell::new(0));
         let mut map = HashMap::new();
+        for val in 0..50 {
+            map.put(CountingKey { val, hashes: hashes.clone() }, val);
+        }
 
-        // Sanity check that hashes are the same
-        assert_eq!(hash(&MyKey::new(1)), hash(&MyKey::new(2)));
-
-        // Insert two different K->V pairs with same hash
-        assert_eq!(map.put(MyKey::new(1), "1"), None);
-        assert_eq!(map.put(MyKey::new(2), "2"), None);
+        let before = hashes.get();
+        let clone = map.clone();
+        assert_eq!(hashes.get(), before);
 
-        assert_eq!(map.get(&MyKey::new(1)), Some(&"1"));
-        assert_eq!(map.get(&MyKey::new(2)), Some(&"2"));
+        // Every entry sits in the same bucket as in the original
+        for (original, cloned) in map.items.iter().zip(clone.items.iter()) {
+            let original: Vec<i32> = original.iter().map(|slot| slot.key.val).collect();
+            let cloned: Vec<i32> = cloned.iter().map(|slot| slot.key.val).collect();
+            assert_eq!(original, cloned);
+        }
     }
 
     #[test]
-    fn test_resize() {
-        let mut map = HashMap::with_options(
-            Options {
-                initial_capacity: Some(16),
-                dynamic_resizing: Some(false),
-                ..Default::default()
-            }.validate().unwrap()
-        );
+    fn test_resize_does_not_rehash() {
+        let hashes = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut map = HashMap::new();
+        for val in 0..50 {
+            map.put(CountingKey { val, hashes: hashes.clone() }, val);
+        }
 
-        let entries: Vec<(String, i32)> = (1..100).map(|i| i.to_string()).zip(1..100).collect();
+        let before = hashes.get();
+        map.resize(200);
+        map.resize_reusing(300);
+        assert_eq!(hashes.get(), before);
+        assert!(map.validate_integrity().is_ok());
+    }
 
-        // Fill map
-        for entry in entries.iter() {
-            map.put(&entry.0[..], entry.1);
+    #[test]
+    fn test_clone_from() {
+        let mut source = HashMap::with_capacity(64);
+        let mut target = HashMap::with_capacity(64);
+        for i in 0..20 {
+            source.put(i, i);
+            target.put(i + 100, i);
         }
 
-        // Resizing map larger doesn't mess up keys
-        map.resize(100);
-        for entry in entries.iter() {
-            assert_eq!(map.get(&&entry.0[..]), Some(&entry.1))
-        }
+        let backing = target.items.as_ptr();
+        target.clone_from(&source);
+
+        assert_eq!(target.items.as_ptr(), backing);
+        assert_eq!(target.capacity(), 64);
+        assert_eq!(target.size(), 20);
+        assert!(target.structural_eq(&source));
+        assert_eq!(target.get(&100), None);
+
+        // Cloning from a map with a different capacity still copies it correctly
+        let mut small = HashMap::with_capacity(2);
+        small.put(1, 1);
+        target.clone_from(&small);
+        assert_eq!(target.capacity(), 2);
+        assert!(target.structural_eq(&small));
+    }
 
-        // Shrinking map doesn't mess up keys
-        map.resize(2);
-        for entry in entries.iter() {
-            assert_eq!(map.get(&&entry.0[..]), Some(&entry.1))
+    // Checks that `iter` reports exactly how many of `len` items are left as it's consumed, and
+    // keeps returning None once it's done.
+    fn check_exact_size<I: ExactSizeIterator + FusedIterator>(mut iter: I, len: usize) {
+        for consumed in 0..len {
+            assert_eq!(iter.size_hint(), (len - consumed, Some(len - consumed)));
+            assert_eq!(iter.len(), len - consumed);
+            assert!(iter.next().is_some());
         }
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert!(iter.next().is_none());
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_iterators_exact_size() {
+        let mut map: HashMap<i32, i32> = (1..100).map(|i| (i, i)).collect();
+        let len = map.size();
+
+        check_exact_size(map.iter(), len);
+        check_exact_size(map.iter_mut(), len);
+        check_exact_size(map.keys(), len);
+        check_exact_size(map.values(), len);
+        check_exact_size(map.values_mut(), len);
+        check_exact_size(map.clone().into_keys(), len);
+        check_exact_size(map.clone().into_values(), len);
+        check_exact_size(map.clone().into_iter(), len);
+        check_exact_size(map.drain(), len);
     }
 
     #[test]
@@ -252,15 +2835,53 @@ mod tests {
         assert_eq!(map.size(), 0);
     }
 
+    #[test]
+    fn test_unhashable_keys() {
+        // Neither Hash nor Eq
+        struct Opaque;
+
+        let mut map: HashMap<Opaque, i32> = HashMap::default();
+        assert!(map.is_empty());
+        assert_eq!(map.size(), 0);
+        assert_eq!(map.iter().count(), 0);
+        map.clear();
+        map.trim_empty_buckets();
+
+        let map: HashMap<Opaque, String> = HashMap::with_capacity(4).map_values(|value: i32| value.to_string());
+        assert_eq!(map.capacity(), 4);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut map = HashMap::new();
+
+        assert!(map.is_empty());
+
+        for i in 0..50 {
+            map.put(i, i);
+        }
+        assert!(!map.is_empty());
+
+        let capacity = map.capacity();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.size(), 0);
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(&1), None);
+
+        map.put(1, 1);
+        assert!(!map.is_empty());
+        assert_eq!(map.get(&1), Some(&1));
+    }
+
     #[test]
     fn test_dynamic_resizing() {
         let initial_capacity = 16;
         let mut map = HashMap::with_options(
-            Options{
-                initial_capacity: Some(initial_capacity),
-                dynamic_resizing: Some(true),
-                ..Default::default()
-            }.validate().unwrap()
+            Options::builder()
+                .initial_capacity(initial_capacity)
+                .dynamic_resizing(true)
+                .build().unwrap()
         );
 
         assert_eq!(map.capacity(), initial_capacity);
@@ -278,24 +2899,362 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resize_count() {
+        // A map sized for its contents never resizes
+        let mut map = HashMap::with_capacity(128);
+        for i in 0..90 {
+            map.put(i, i);
+        }
+        assert_eq!(map.resize_count(), 0);
+
+        // An undersized one does
+        let mut map = HashMap::with_capacity(4);
+        for i in 0..90 {
+            map.put(i, i);
+        }
+        assert_eq!(map.resize_count(), 5);
+
+        map.resize(2);
+        assert_eq!(map.resize_count(), 6);
+    }
+
+    #[test]
+    fn test_lazy_buckets_option() {
+        let initial_capacity = 1 << 20;
+        let mut map = HashMap::with_options(
+            Options::builder()
+                .initial_capacity(initial_capacity)
+                .lazy_buckets(true)
+                .build().unwrap()
+        );
+
+        // Nothing is allocated until the first insertion
+        assert_eq!(map.items.capacity(), 0);
+        assert_eq!(map.capacity(), initial_capacity);
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.pop(&1), None);
+        assert_eq!(map.items.capacity(), 0);
+
+        map.put(1, 1);
+        assert_eq!(map.items.len(), initial_capacity);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.resize_count(), 0);
+    }
+
+    #[test]
+    fn test_fibonacci_hashing_option() {
+        let options = |fibonacci_hashing| Options::builder()
+            .initial_capacity(16)
+            .fibonacci_hashing(fibonacci_hashing)
+            .build().unwrap();
+        let mut modulo = HashMap::with_options_and_hasher(options(false), IdentityState::default());
+        let mut fibonacci = HashMap::with_options_and_hasher(options(true), IdentityState::default());
+
+        // Multiples of the capacity all hash to the same bucket by modulo
+        for i in 0..10u64 {
+            modulo.put(i * 16, i);
+            fibonacci.put(i * 16, i);
+        }
+        assert_eq!(modulo.worst_case_lookup().map(|(_, length)| length), Some(10));
+        assert!(fibonacci.worst_case_lookup().map(|(_, length)| length) <= Some(2));
+
+        fibonacci.resize(37);
+        assert!(fibonacci.validate_integrity().is_ok());
+        assert_eq!(fibonacci.get(&48), Some(&3));
+    }
+
+    #[test]
+    fn test_try_with_options() {
+        let options = Options::builder()
+            .initial_capacity(4)
+            .load_factor(0.5)
+            .dynamic_resizing(true);
+        let mut map = HashMap::try_with_options(options).unwrap();
+        map.put(1, 1);
+        assert_eq!(map.capacity(), 4);
+        map.put(2, 2);
+        assert_eq!(map.capacity(), 8);
+
+        let error = HashMap::<i32, i32>::try_with_options(Options::builder().load_factor(0.0)).err();
+        assert_eq!(error, Some(OptionsError::InvalidLoadFactor(0.0)));
+    }
+
+    #[test]
+    fn test_set_load_factor() {
+        let mut map = HashMap::with_capacity(32);
+        for i in 0..12 {
+            map.put(i, i);
+        }
+
+        // Relaxing the load factor never needs a resize
+        assert_eq!(map.set_load_factor(0.9), Ok(()));
+        assert_eq!(map.options().load_factor(), 0.9);
+        assert_eq!(map.capacity(), 32);
+
+        // Tightening it past how full the map already is grows it
+        assert_eq!(map.set_load_factor(0.25), Ok(()));
+        assert_eq!(map.capacity(), 64);
+        assert_eq!(map.resize_count(), 1);
+        assert!(map.validate_integrity().is_ok());
+
+        assert_eq!(map.set_load_factor(0.0), Err(OptionsError::InvalidLoadFactor(0.0)));
+        assert_eq!(map.options().load_factor(), 0.25);
+    }
+
+    #[test]
+    fn test_set_dynamic_resizing() {
+        let mut map = HashMap::with_capacity(4);
+        map.set_dynamic_resizing(false);
+        for i in 0..20 {
+            map.put(i, i);
+        }
+        assert_eq!(map.capacity(), 4);
+
+        map.set_dynamic_resizing(true);
+        assert!(map.options().dynamic_resizing());
+        assert_eq!(map.capacity(), 32);
+        assert_eq!(map.resize_count(), 1);
+        assert_eq!(map.get(&19), Some(&19));
+    }
+
+    #[test]
+    fn test_auto_shrink() {
+        let mut map = HashMap::new();
+        for i in 0..1000 {
+            map.put(i, i);
+        }
+        let peak = map.capacity();
+
+        // Removing most entries shrinks the map, but not all the way down at once
+        for i in 0..900 {
+            map.pop(&i);
+        }
+        assert!(map.capacity() < peak);
+        assert!(map.capacity() > options::DEFAULT_CAPACITY);
+        assert!(map.validate_integrity().is_ok());
+        assert_eq!(map.get(&950), Some(&950));
+
+        // Emptying it entirely goes back to the initial capacity
+        for i in 900..1000 {
+            map.pop(&i);
+        }
+        assert_eq!(map.capacity(), options::DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn test_auto_shrink_off() {
+        let fill = |options| {
+            let mut map = HashMap::with_options(options);
+            map.extend((0..1000).map(|i| (i, i)));
+            let peak = map.capacity();
+            map.extract_if(|key, _| *key >= 10).for_each(drop);
+            map.pop(&0);
+            (peak, map.capacity())
+        };
+
+        let (peak, capacity) = fill(Options::builder().auto_shrink(false).build().unwrap());
+        assert_eq!(capacity, peak);
+
+        // Needs dynamic resizing as well
+        let (peak, capacity) = fill(Options::builder().dynamic_resizing(false).initial_capacity(2048).build().unwrap());
+        assert_eq!(capacity, peak);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut map = HashMap::with_options(Options::builder().auto_shrink(false).build().unwrap());
+        map.extend((0..1000).map(|i| (i, i)));
+        map.extract_if(|key, _| *key >= 30).for_each(drop);
+        assert!(map.capacity() > 1000);
+
+        map.shrink_to_fit();
+        // 30 entries at a load factor of 0.75 need 41 buckets
+        assert_eq!(map.capacity(), 41);
+        assert!(!map.exceeds_threshold(map.size()));
+        assert!(map.validate_integrity().is_ok());
+        assert_eq!(map.get(&29), Some(&29));
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 1);
+        map.put(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn test_shrink_to() {
+        let mut map = HashMap::with_capacity(256);
+        map.extend((0..10).map(|i| (i, i)));
+
+        map.shrink_to(100);
+        assert_eq!(map.capacity(), 100);
+        assert_eq!(map.resize_count(), 1);
+
+        // Never grows the map
+        map.shrink_to(200);
+        assert_eq!(map.capacity(), 100);
+        assert_eq!(map.resize_count(), 1);
+
+        // Nor shrinks it past what its entries need
+        map.shrink_to(2);
+        assert_eq!(map.capacity(), 14);
+        assert!(map.validate_integrity().is_ok());
+    }
+
+    static EMPTY: HashMap<&str, i32> = HashMap::new();
+
+    #[test]
+    fn test_const_new() {
+        assert!(EMPTY.is_empty());
+        assert_eq!(EMPTY.get("a"), None);
+        assert!(!EMPTY.is_allocated());
+
+        let mut map = HashMap::new();
+        assert!(!map.is_allocated());
+        map.put("a", 1);
+        assert!(map.is_allocated());
+        assert_eq!(map.capacity(), options::DEFAULT_CAPACITY);
+        assert_eq!(map.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn test_extend_existing_keys() {
+        let mut map: HashMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        let capacity = map.capacity();
+
+        // Overwriting every key only reserves for half of them, which the map already has room for
+        map.extend((0..100).map(|i| (i, i * 2)));
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(&50), Some(&100));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut map = HashMap::new();
+        map.put(0, 0);
+
+        map.reserve(100);
+        assert_eq!(map.resize_count(), 1);
+        let capacity = map.capacity();
+        assert_eq!(capacity, 256);
+
+        // The reserved entries fit without any more resizing
+        for i in 1..=100 {
+            map.put(i, i);
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.resize_count(), 1);
+
+        // Nothing to do when there's already room
+        map.reserve(10);
+        assert_eq!(map.resize_count(), 1);
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut map = HashMap::new();
+        map.put(0, 0);
+
+        assert!(map.try_reserve(100).is_ok());
+        assert_eq!(map.capacity(), 256);
+        assert_eq!(map.resize_count(), 1);
+
+        // Far too many buckets to allocate, so the map is left alone
+        assert!(map.try_reserve(usize::MAX).is_err());
+        assert_eq!(map.capacity(), 256);
+        assert_eq!(map.resize_count(), 1);
+        assert_eq!(map.get(&0), Some(&0));
+    }
+
+    #[test]
+    fn test_incremental_resizing() {
+        let options = Options::builder()
+            .initial_capacity(16)
+            .incremental_resizing(true)
+            .build().unwrap();
+        let mut map = HashMap::with_options(options);
+        for i in 0..11 {
+            map.put(i, i);
+        }
+        assert_eq!(map.resize_count(), 0);
+
+        // Growing only sets up the new table, leaving the entries where they are for now
+        map.put(11, 11);
+        assert_eq!(map.resize_count(), 1);
+        assert_eq!(map.capacity(), 32);
+        assert!(map.is_migrating());
+        for i in 0..12 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        assert_eq!(map.iter().count(), 12);
+        assert_eq!(map.validate_integrity(), Ok(()));
+
+        // Each modification moves a few more buckets, until the old table is gone
+        assert_eq!(map.pop(&3), Some(3));
+        assert!(map.is_migrating());
+        assert_eq!(map.validate_integrity(), Ok(()));
+        *map.get_mut(&5).unwrap() = 50;
+        assert!(!map.is_migrating());
+        assert_eq!(map.validate_integrity(), Ok(()));
+        assert_eq!(map.get(&5), Some(&50));
+        assert_eq!(map.size(), 11);
+    }
+
+    #[test]
+    fn test_incremental_resizing_overlap() {
+        // Growing by so little leaves hardly any room before the next growth, so each move has to
+        // be done in far fewer, bigger steps
+        let options = Options::builder()
+            .initial_capacity(16)
+            .growth_factor(1.1)
+            .incremental_resizing(true)
+            .build().unwrap();
+        let mut map = HashMap::with_options(options);
+        for i in 0..1000 {
+            if map.exceeds_threshold(map.size() + 1) {
+                // The step the growing insertion does first is always enough to finish the last move
+                map.migrate_step();
+                assert!(!map.is_migrating());
+            }
+            map.put(i, i);
+            assert_eq!(map.validate_integrity(), Ok(()));
+        }
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort();
+        assert_eq!(entries, (0..1000).map(|i| (i, i)).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_capacity_option() {
         let initial_capacity = 2;
         let map: HashMap<i32, i32> = HashMap::with_options(
-            Options { initial_capacity: Some(initial_capacity), ..Default::default() }.validate().unwrap());
+            Options::builder().initial_capacity(initial_capacity).build().unwrap());
 
         assert_eq!(map.capacity(), initial_capacity)
     }
 
+    #[test]
+    fn test_try_with_capacity() {
+        assert!(HashMap::<i32, i32>::try_with_capacity(0).is_err());
+
+        let map: HashMap<i32, i32> = HashMap::try_with_capacity(7).unwrap();
+        assert_eq!(map.capacity(), 7);
+    }
+
     #[test]
     fn test_dynamic_resizing_off() {
         let initial_capacity = 3;
         let mut map: HashMap<i32, i32> = HashMap::with_options(
-            Options {
-                initial_capacity: Some(initial_capacity),
-                load_factor: Some(0.5),
-                dynamic_resizing: Some(false)
-             }.validate().unwrap()
+            Options::builder()
+                .initial_capacity(initial_capacity)
+                .load_factor(0.5)
+                .dynamic_resizing(false)
+                .build().unwrap()
         );
 
         map.put(1, 1);
@@ -305,15 +3264,72 @@ mod tests {
         assert_eq!(map.capacity(), initial_capacity);
     }
 
+    #[test]
+    fn test_adaptive_load_factor_option() {
+        // Counts how many entries a map of this capacity takes before it resizes
+        let fill_before_resize = |adaptive| {
+            let initial_capacity = options::ADAPTIVE_LARGE_CAPACITY;
+            let mut map = HashMap::with_options(
+                Options::builder()
+                    .initial_capacity(initial_capacity)
+                    .adaptive_load_factor(adaptive)
+                    .build().unwrap()
+            );
+
+            let mut i = 0;
+            while map.capacity() == initial_capacity {
+                map.put(i, i);
+                i += 1;
+            }
+            map.size()
+        };
+
+        let fixed = fill_before_resize(false);
+        let adaptive = fill_before_resize(true);
+        assert_eq!(fixed, options::ADAPTIVE_LARGE_CAPACITY * 3 / 4);
+        assert_eq!(adaptive, options::ADAPTIVE_LARGE_CAPACITY / 2);
+    }
+
+    #[test]
+    fn test_adaptive_load_factor_small() {
+        let mut map = HashMap::with_options(
+            Options::builder().initial_capacity(4).adaptive_load_factor(true).build().unwrap()
+        );
+
+        // Small maps behave the same as with a fixed load factor
+        map.put(1, 1);
+        map.put(2, 2);
+        assert_eq!(map.capacity(), 4);
+        map.put(3, 3);
+        assert_ne!(map.capacity(), 4);
+    }
+
+    #[test]
+    fn test_growth_factor_option() {
+        let mut map = HashMap::with_options(
+            Options::builder().initial_capacity(16).growth_factor(1.5).build().unwrap()
+        );
+
+        let mut capacities = vec![map.capacity()];
+        for i in 0..200 {
+            map.put(i, i);
+            if map.capacity() != *capacities.last().unwrap() {
+                capacities.push(map.capacity());
+            }
+        }
+
+        assert_eq!(capacities, vec![16, 24, 36, 54, 81, 122, 183, 275]);
+    }
+
     #[test]
     fn test_load_factor_option() {
         let initial_capacity = 4;
         let mut map: HashMap<i32, i32> = HashMap::with_options(
-            Options {
-                initial_capacity: Some(initial_capacity),
-                load_factor: Some(0.5),
-                dynamic_resizing: Some(true)
-             }.validate().unwrap()
+            Options::builder()
+                .initial_capacity(initial_capacity)
+                .load_factor(0.5)
+                .dynamic_resizing(true)
+                .build().unwrap()
         );
 
         map.put(1, 1);
@@ -321,11 +3337,11 @@ mod tests {
         assert_ne!(map.capacity(), initial_capacity);
 
         let mut map: HashMap<i32, i32> = HashMap::with_options(
-            Options {
-                initial_capacity: Some(initial_capacity),
-                load_factor: Some(0.75),
-                dynamic_resizing: Some(true)
-             }.validate().unwrap()
+            Options::builder()
+                .initial_capacity(initial_capacity)
+                .load_factor(0.75)
+                .dynamic_resizing(true)
+                .build().unwrap()
         );
 
         map.put(1, 1);
@@ -0,0 +1,182 @@
+//! Tools for inspecting how entries are distributed among a [HashMap]'s buckets.
+
+use std::hash::{BuildHasher, Hash};
+
+use super::HashMap;
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Returns a key from the map's longest bucket along with that bucket's length, or [None] if
+    /// the map is empty. The length is the most key comparisons any lookup can take, so it shows
+    /// how far lookups degrade when many keys hash to the same bucket.
+    pub fn worst_case_lookup(&self) -> Option<(&K, usize)> {
+        self.buckets()
+            .max_by_key(|bucket| bucket.len())
+            .and_then(|bucket| bucket.last().map(|slot| (&slot.key, bucket.len())))
+    }
+
+    /// Gets a reference to the value corresponding to a key like [HashMap::get], along with how
+    /// many keys in the key's bucket had to be compared against it to find it (or to find that
+    /// it's missing). With no collisions this is at most one, but it grows with the bucket.
+    pub fn get_with_probes(&self, key: &K) -> (Option<&V>, usize) {
+        let (slot, probes) = self.probe(key);
+        (slot.map(|slot| &slot.value), probes)
+    }
+
+    /// Splits the map's keys into those in buckets holding at least `threshold` entries, which are
+    /// the most affected by collisions, and the rest.
+    pub fn partition_by_bucket_load(&self, threshold: usize) -> (Vec<&K>, Vec<&K>) {
+        let mut hot = Vec::new();
+        let mut cold = Vec::new();
+        for bucket in self.buckets() {
+            let keys = if bucket.len() >= threshold { &mut hot } else { &mut cold };
+            keys.extend(bucket.iter().map(|slot| &slot.key));
+        }
+        (hot, cold)
+    }
+
+    /// Returns every pair of keys which currently share a bucket. Each pair appears once, in no
+    /// particular order. This takes time proportional to the sum of each bucket's length squared,
+    /// so it's intended for small maps.
+    pub fn collision_pairs(&self) -> Vec<(&K, &K)> {
+        let mut pairs = Vec::new();
+        for bucket in self.buckets() {
+            for (position, slot) in bucket.iter().enumerate() {
+                for other in bucket[position + 1..].iter() {
+                    pairs.push((&slot.key, &other.key));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::hash_map::DefaultHasher, hash::{BuildHasherDefault, Hasher}};
+
+    use super::*;
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct CollidingKey(i32);
+
+    impl Hash for CollidingKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            state.write_i32(1); // Always give same hash
+        }
+    }
+
+    #[test]
+    fn test_worst_case_lookup_colliding() {
+        let mut map = HashMap::new();
+
+        assert_eq!(map.worst_case_lookup(), None);
+
+        for i in 0..20 {
+            map.put(CollidingKey(i), i);
+        }
+
+        let (key, length) = map.worst_case_lookup().unwrap();
+        assert!(map.get(key).is_some());
+        assert_eq!(length, map.size());
+    }
+
+    #[test]
+    fn test_worst_case_lookup_distributed() {
+        // Use a fixed hasher so the distribution is the same every run
+        let mut map = HashMap::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+
+        for i in 0..100 {
+            map.put(i, i);
+        }
+
+        let (key, length) = map.worst_case_lookup().unwrap();
+        assert!(map.get(key).is_some());
+        assert!(length <= 5);
+    }
+
+    #[test]
+    fn test_collision_pairs() {
+        let mut map = HashMap::new();
+
+        map.put(CollidingKey(1), 1);
+        map.put(CollidingKey(2), 2);
+        map.put(CollidingKey(3), 3);
+
+        let mut pairs: Vec<(i32, i32)> = map.collision_pairs().into_iter()
+            .map(|(a, b)| (a.0.min(b.0), a.0.max(b.0)))
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(1, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn test_collision_pairs_distributed() {
+        let mut map = HashMap::with_capacity(64);
+
+        // Only add keys which land in a bucket of their own
+        let mut keys: Vec<i32> = Vec::new();
+        for key in 0.. {
+            if keys.len() == 10 {
+                break;
+            }
+            if !keys.iter().any(|other| map.keys_collide(other, &key)) {
+                map.put(key, key);
+                keys.push(key);
+            }
+        }
+
+        assert!(map.collision_pairs().is_empty());
+    }
+
+    #[test]
+    fn test_get_with_probes() {
+        let mut map = HashMap::new();
+
+        for i in 0..5 {
+            map.put(CollidingKey(i), i);
+        }
+
+        assert_eq!(map.get_with_probes(&CollidingKey(0)), (Some(&0), 1));
+        assert_eq!(map.get_with_probes(&CollidingKey(4)), (Some(&4), 5));
+        assert_eq!(map.get_with_probes(&CollidingKey(5)), (None, 5));
+    }
+
+    #[test]
+    fn test_get_with_probes_empty_bucket() {
+        let mut map = HashMap::with_capacity(64);
+        map.put(0, 0);
+
+        let missing = (1..).find(|key| !map.keys_collide(&0, key)).unwrap();
+        assert_eq!(map.get_with_probes(&missing), (None, 0));
+        assert_eq!(map.get_with_probes(&0), (Some(&0), 1));
+    }
+
+    // Keys below 10 all hash the same, the rest hash normally
+    #[derive(PartialEq, Eq, Debug)]
+    struct ClusteredKey(i32);
+
+    impl Hash for ClusteredKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            state.write_i32(if self.0 < 10 { -1 } else { self.0 });
+        }
+    }
+
+    #[test]
+    fn test_partition_by_bucket_load() {
+        // Use a fixed hasher so the keys that hash normally can't happen to collide
+        let mut map = HashMap::with_capacity_and_hasher(256, BuildHasherDefault::<DefaultHasher>::default());
+
+        for i in 0..20 {
+            map.put(ClusteredKey(i), i);
+        }
+
+        let (hot, cold) = map.partition_by_bucket_load(5);
+        let mut hot: Vec<i32> = hot.into_iter().map(|key| key.0).collect();
+        let mut cold: Vec<i32> = cold.into_iter().map(|key| key.0).collect();
+        hot.sort();
+        cold.sort();
+
+        assert_eq!(hot, (0..10).collect::<Vec<_>>());
+        assert_eq!(cold, (10..20).collect::<Vec<_>>());
+    }
+}
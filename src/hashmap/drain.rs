@@ -0,0 +1,130 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, DefaultState};
+
+/// An [Iterator] which removes every entry from a [HashMap], returning them with ownership.
+/// If the iterator is dropped before it's finished, the remaining entries are dropped too.
+/// Unless the map has auto shrinking enabled, it keeps its capacity and its buckets'
+/// allocations, so it can be refilled without reallocating. Otherwise, it shrinks back to its
+/// initial capacity once it's been drained.
+pub struct Drain<'a, K, V, S = DefaultState> {
+    map: &'a mut HashMap<K, V, S>,
+    bucket: usize
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get a [Drain] iterator for this [HashMap].
+    pub fn drain(&mut self) -> Drain<'_, K, V, S> {
+        self.finish_migration();
+        Drain { map: self, bucket: 0 }
+    }
+}
+
+impl<'a, K, V, S> Iterator for Drain<'a, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(bucket) = self.map.items.get_mut(self.bucket) {
+            if let Some(slot) = bucket.pop() {
+                self.map.size -= 1;
+                return Some((slot.key, slot.value));
+            }
+            self.bucket += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.size, Some(self.map.size))
+    }
+}
+
+impl<'a, K, V, S> Drop for Drain<'a, K, V, S> {
+    fn drop(&mut self) {
+        for bucket in self.map.items.iter_mut() {
+            bucket.clear();
+        }
+        self.map.size = 0;
+        self.map.shrink_if_sparse();
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Drain<'a, K, V, S> {}
+
+impl<'a, K, V, S> FusedIterator for Drain<'a, K, V, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashmap::options::{Options, DEFAULT_CAPACITY};
+
+    fn make_map() -> HashMap<i32, i32> {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, i * 10);
+        }
+        map
+    }
+
+    #[test]
+    fn test_iterator() {
+        let mut map = make_map();
+
+        // Map is unordered, so sort before comparing
+        let mut items: Vec<(i32, i32)> = map.drain().collect();
+        items.sort();
+
+        let expected: Vec<(i32, i32)> = (1..100).map(|i| (i, i * 10)).collect();
+        assert_eq!(items, expected);
+        assert_eq!(map.size(), 0);
+        assert_eq!(map.get(&1), None);
+
+        // The map is still usable afterwards
+        map.put(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn test_keeps_allocation() {
+        let mut map = HashMap::with_options(Options::builder().auto_shrink(false).build().unwrap());
+        map.extend(make_map());
+
+        let capacity = map.capacity();
+        let backing = map.items.as_ptr();
+        let bucket_capacities: Vec<usize> = map.items.iter().map(|bucket| bucket.capacity()).collect();
+
+        map.drain().for_each(drop);
+
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.items.as_ptr(), backing);
+        assert_eq!(map.items.iter().map(|bucket| bucket.capacity()).collect::<Vec<_>>(), bucket_capacities);
+    }
+
+    #[test]
+    fn test_shrinks_after_drain() {
+        let mut map = make_map();
+        assert!(map.capacity() > DEFAULT_CAPACITY);
+
+        map.drain().for_each(drop);
+
+        assert_eq!(map.capacity(), DEFAULT_CAPACITY);
+        assert!(map.validate_integrity().is_ok());
+    }
+
+    #[test]
+    fn test_partial_drain() {
+        let mut map = make_map();
+
+        let mut drain = map.drain();
+        assert_eq!(drain.size_hint(), (99, Some(99)));
+        drain.next();
+        drain.next();
+        assert_eq!(drain.size_hint(), (97, Some(97)));
+        drop(drain);
+
+        assert_eq!(map.size(), 0);
+        assert!(map.items.iter().all(|bucket| bucket.is_empty()));
+    }
+}
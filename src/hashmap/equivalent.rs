@@ -0,0 +1,107 @@
+//! Lets a [HashMap](super::HashMap) be queried with types other than its key type.
+
+use std::borrow::Borrow;
+
+/// A type which can be compared with keys of type `K`, so it can be used to look them up in a
+/// [HashMap](super::HashMap), e.g. with [HashMap::get](super::HashMap::get).
+///
+/// Anything `K` can be borrowed as already implements this, so `&str` can be used to look up
+/// [String] keys. Implement it directly to look keys up by some other representation, such as a
+/// pair of references for tuple keys. Equivalent values must hash the same as the keys they're
+/// equivalent to, or they won't be found.
+pub trait Equivalent<K: ?Sized> {
+    /// Returns whether `self` is equivalent to `key`.
+    fn equivalent(&self, key: &K) -> bool;
+}
+
+impl<Q: Eq + ?Sized, K: Borrow<Q> + ?Sized> Equivalent<K> for Q {
+    fn equivalent(&self, key: &K) -> bool {
+        self == key.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{Hash, Hasher};
+
+    use crate::hashmap::HashMap;
+
+    use super::*;
+
+    // A string key which ignores ASCII case
+    #[derive(Debug)]
+    struct Uncased(String);
+
+    // A borrowed string used to look up `Uncased` keys
+    struct UncasedStr<'a>(&'a str);
+
+    fn hash_uncased<H: Hasher>(s: &str, state: &mut H) {
+        for byte in s.bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+        state.write_u8(0xff);
+    }
+
+    impl Hash for Uncased {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            hash_uncased(&self.0, state);
+        }
+    }
+
+    impl Hash for UncasedStr<'_> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            hash_uncased(self.0, state);
+        }
+    }
+
+    impl PartialEq for Uncased {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.eq_ignore_ascii_case(&other.0)
+        }
+    }
+
+    impl Eq for Uncased {}
+
+    impl Equivalent<Uncased> for UncasedStr<'_> {
+        fn equivalent(&self, key: &Uncased) -> bool {
+            self.0.eq_ignore_ascii_case(&key.0)
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup() {
+        let mut map = HashMap::new();
+        map.put(Uncased(String::from("Content-Type")), "text/plain");
+
+        assert_eq!(map.get(&UncasedStr("content-type")), Some(&"text/plain"));
+        assert_eq!(map.get(&UncasedStr("CONTENT-TYPE")), Some(&"text/plain"));
+        assert!(!map.contains_key(&UncasedStr("content-length")));
+
+        assert_eq!(map.pop(&UncasedStr("CONTENT-type")), Some("text/plain"));
+        assert!(map.is_empty());
+    }
+
+    // A pair of borrowed halves, hashed the same way as the tuple it stands in for
+    #[derive(Hash)]
+    struct PairRef<'a>(&'a u32, &'a u32);
+
+    impl Equivalent<(u32, u32)> for PairRef<'_> {
+        fn equivalent(&self, key: &(u32, u32)) -> bool {
+            *self.0 == key.0 && *self.1 == key.1
+        }
+    }
+
+    #[test]
+    fn test_borrowed_pair_lookup() {
+        let mut map = HashMap::new();
+        for x in 0..10 {
+            map.put((x, x * 2), x);
+        }
+
+        let (x, y) = (3, 6);
+        assert_eq!(map.get(&PairRef(&x, &y)), Some(&3));
+        assert_eq!(map.get(&PairRef(&y, &x)), None);
+        assert_eq!(map.pop(&PairRef(&x, &y)), Some(3));
+        assert_eq!(map.get(&(3, 6)), None);
+    }
+}
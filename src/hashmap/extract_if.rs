@@ -0,0 +1,125 @@
+use std::hash::{BuildHasher, Hash};
+
+use super::{HashMap, DefaultState};
+
+/// An [Iterator] which removes the entries of a [HashMap] matching a predicate, returning them
+/// with ownership. Entries are only checked and removed as the iterator is advanced, so if it's
+/// dropped before it's finished, the entries it hasn't reached yet stay in the map.
+pub struct ExtractIf<'a, K, V, F, S = DefaultState> where F: FnMut(&K, &mut V) -> bool {
+    map: &'a mut HashMap<K, V, S>,
+    bucket: usize,
+    position: usize,
+    predicate: F
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Get an [ExtractIf] iterator for this [HashMap], which removes and returns every entry for
+    /// which `predicate` returns true. The predicate may also modify the values of entries which
+    /// are kept.
+    pub fn extract_if<F>(&mut self, predicate: F) -> ExtractIf<'_, K, V, F, S> where F: FnMut(&K, &mut V) -> bool {
+        self.finish_migration();
+        ExtractIf { map: self, bucket: 0, position: 0, predicate }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, F, S: BuildHasher> Iterator for ExtractIf<'a, K, V, F, S> where F: FnMut(&K, &mut V) -> bool {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bucket < self.map.items.len() {
+            while let Some(slot) = self.map.items[self.bucket].get_mut(self.position) {
+                if (self.predicate)(&slot.key, &mut slot.value) {
+                    // Removing the slot moves a later one into its position, so stay put
+                    self.map.size -= 1;
+                    let slot = self.map.remove_at(self.bucket, self.position);
+                    return Some((slot.key, slot.value));
+                }
+                self.position += 1;
+            }
+            self.bucket += 1;
+            self.position = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.map.size))
+    }
+}
+
+impl<'a, K, V, F, S> Drop for ExtractIf<'a, K, V, F, S> where F: FnMut(&K, &mut V) -> bool {
+    fn drop(&mut self) {
+        // Shrinking moves entries between buckets, so it has to wait until the iterator is done
+        self.map.shrink_if_sparse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_map() -> HashMap<i32, i32> {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, i * 10);
+        }
+        map
+    }
+
+    #[test]
+    fn test_iterator() {
+        let mut map = make_map();
+
+        // Map is unordered, so sort before comparing
+        let mut extracted: Vec<(i32, i32)> = map.extract_if(|key, _| key % 2 == 0).collect();
+        extracted.sort();
+
+        let expected: Vec<(i32, i32)> = (1..100).filter(|i| i % 2 == 0).map(|i| (i, i * 10)).collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(map.size(), 50);
+        for i in 1..100 {
+            let expected = if i % 2 == 0 { None } else { Some(&(i * 10)) };
+            assert_eq!(map.get(&i), expected);
+        }
+    }
+
+    #[test]
+    fn test_modify_kept_values() {
+        let mut map = make_map();
+
+        let extracted = map.extract_if(|_, value| {
+            *value += 1;
+            *value > 500
+        }).count();
+
+        assert_eq!(extracted, 50);
+        assert_eq!(map.size(), 49);
+        assert_eq!(map.get(&1), Some(&11));
+    }
+
+    #[test]
+    fn test_partial_extract() {
+        let mut map = make_map();
+
+        let mut extract = map.extract_if(|_, _| true);
+        extract.next();
+        extract.next();
+        drop(extract);
+
+        assert_eq!(map.size(), 97);
+    }
+
+    #[test]
+    fn test_migrate_entries() {
+        let mut map = make_map();
+
+        let other: HashMap<i32, i32> = map.extract_if(|key, _| *key > 90).collect();
+
+        assert_eq!(other.size(), 9);
+        assert_eq!(map.size(), 90);
+        assert_eq!(other.get(&95), Some(&950));
+        assert_eq!(map.get(&95), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Ready-made [BuildHasher](std::hash::BuildHasher)s which trade some of [DefaultState](super::DefaultState)'s resistance
+//! to deliberately colliding keys for speed. Apart from [IdentityState], each one is behind its
+//! own cargo feature. They're selected when the map is created, e.g.
+//! `HashMap::with_hasher(FxBuildHasher::default())`.
+
+#[cfg(feature = "ahash")]
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasherDefault, Hasher};
+#[cfg(feature = "ahash")]
+use std::hash::BuildHasher;
+
+/// A hasher which doesn't hash at all: an integer key is its own hash. This is only worth using
+/// for integer keys which are already well distributed, like IDs or handles, and is what
+/// [IntMap](super::IntMap) uses.
+///
+/// Keys which write more than a single integer are folded together a word at a time, which is
+/// fast but collides easily.
+#[derive(Default, Clone, Copy)]
+pub struct IdentityHasher {
+    hash: u64
+}
+
+/// The [BuildHasher](std::hash::BuildHasher) for [IdentityHasher].
+pub type IdentityState = BuildHasherDefault<IdentityHasher>;
+
+impl IdentityHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        // The hash starts at zero, so a single word is left as it is
+        self.hash = self.hash.rotate_left(8) ^ word;
+    }
+}
+
+impl Hasher for IdentityHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.add_to_hash(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// A very fast, non-cryptographic hasher in the style of FxHash, which mixes each word of input
+/// into the hash with a rotate, xor, and multiply. It has no seed, so the same keys always
+/// collide; use it for trusted keys only.
+#[cfg(feature = "fxhash")]
+#[derive(Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64
+}
+
+/// The [BuildHasher](std::hash::BuildHasher) for [FxHasher].
+#[cfg(feature = "fxhash")]
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+#[cfg(feature = "fxhash")]
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+#[cfg(feature = "fxhash")]
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let mut rest = chunks.remainder();
+        if rest.len() >= 4 {
+            self.add_to_hash(u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64);
+            rest = &rest[4..];
+        }
+        for byte in rest {
+            self.add_to_hash(*byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        // The multiply mixes input upwards, leaving the low bits poorly mixed. Maps pick buckets
+        // with the low bits, so rotate the better mixed high bits down.
+        self.hash.rotate_left(26)
+    }
+}
+
+/// A fast hasher in the style of aHash's portable fallback, which mixes input in with folded
+/// 128-bit multiplies. Unlike [FxHasher] it's keyed, so with [AHashState::new] which keys
+/// collide changes from map to map.
+#[cfg(feature = "ahash")]
+#[derive(Clone, Copy)]
+pub struct AHasher {
+    buffer: u64,
+    pad: u64,
+    extra_keys: [u64; 2]
+}
+
+/// The [BuildHasher] for [AHasher], holding the keys each hasher it builds starts from.
+#[cfg(feature = "ahash")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AHashState {
+    keys: [u64; 4]
+}
+
+#[cfg(feature = "ahash")]
+const MULTIPLE: u64 = 6364136223846793005;
+
+/// Multiplies `a` and `b` into 128 bits, then folds the halves of the result together.
+#[cfg(feature = "ahash")]
+fn folded_multiply(a: u64, b: u64) -> u64 {
+    let result = (a as u128).wrapping_mul(b as u128);
+    (result as u64) ^ ((result >> 64) as u64)
+}
+
+#[cfg(feature = "ahash")]
+impl AHashState {
+    /// Creates an [AHashState] with randomly chosen keys.
+    pub fn new() -> Self {
+        // Borrow the standard library's per-process randomness rather than finding our own
+        let random = RandomState::new();
+        let mut keys = [0; 4];
+        for (i, key) in keys.iter_mut().enumerate() {
+            *key = random.hash_one(i);
+        }
+        AHashState { keys }
+    }
+
+    /// Creates an [AHashState] with fixed keys, so hashing is the same every run.
+    pub fn with_seeds(k0: u64, k1: u64, k2: u64, k3: u64) -> Self {
+        AHashState { keys: [k0, k1, k2, k3] }
+    }
+}
+
+#[cfg(feature = "ahash")]
+impl Default for AHashState {
+    fn default() -> Self {
+        AHashState::new()
+    }
+}
+
+#[cfg(feature = "ahash")]
+impl BuildHasher for AHashState {
+    type Hasher = AHasher;
+
+    fn build_hasher(&self) -> AHasher {
+        AHasher {
+            buffer: self.keys[0],
+            pad: self.keys[1],
+            extra_keys: [self.keys[2], self.keys[3]]
+        }
+    }
+}
+
+#[cfg(feature = "ahash")]
+impl AHasher {
+    fn update(&mut self, word: u64) {
+        self.buffer = folded_multiply(word ^ self.buffer, MULTIPLE);
+    }
+
+    fn large_update(&mut self, low: u64, high: u64) {
+        let combined = folded_multiply(low ^ self.extra_keys[0], high ^ self.extra_keys[1]);
+        self.buffer = (self.buffer.wrapping_add(self.pad) ^ combined).rotate_left(23);
+    }
+}
+
+#[cfg(feature = "ahash")]
+impl Hasher for AHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        // Mixing in the length first keeps e.g. "ab" + "c" and "a" + "bc" apart
+        self.buffer = self.buffer.wrapping_add(bytes.len() as u64).wrapping_mul(MULTIPLE);
+
+        let mut chunks = bytes.chunks_exact(16);
+        for chunk in &mut chunks {
+            let low = u64::from_le_bytes(chunk[..8].try_into().unwrap());
+            let high = u64::from_le_bytes(chunk[8..].try_into().unwrap());
+            self.large_update(low, high);
+        }
+
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut padded = [0; 16];
+            padded[..rest.len()].copy_from_slice(rest);
+            let low = u64::from_le_bytes(padded[..8].try_into().unwrap());
+            let high = u64::from_le_bytes(padded[8..].try_into().unwrap());
+            self.large_update(low, high);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.update(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.update(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.update(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.update(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.update(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        let rotation = (self.buffer & 63) as u32;
+        folded_multiply(self.buffer, self.pad).rotate_left(rotation)
+    }
+}
+
+// Hashes like the standard library's default hasher, counting how many hashers it builds, so
+// tests can check how often a map hashes its keys.
+#[cfg(test)]
+#[derive(Default, Clone)]
+pub(crate) struct CountingState(pub(crate) std::cell::Cell<usize>);
+
+#[cfg(test)]
+impl std::hash::BuildHasher for CountingState {
+    type Hasher = std::hash::DefaultHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        self.0.set(self.0.get() + 1);
+        std::hash::DefaultHasher::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasher;
+
+    use crate::hashmap::{HashMap, IntMap};
+
+    use super::*;
+
+    /// Puts enough keys in `map` to make it resize a few times, then checks they can all be
+    /// found again.
+    #[cfg(any(feature = "fxhash", feature = "ahash"))]
+    fn check_map<S: BuildHasher>(mut map: HashMap<String, usize, S>) {
+        for i in 0..500 {
+            assert_eq!(map.put(i.to_string(), i), None);
+        }
+        assert!(map.resize_count() > 0);
+        for i in 0..500 {
+            assert_eq!(map.get(i.to_string().as_str()), Some(&i));
+        }
+        assert!(map.validate_integrity().is_ok());
+    }
+
+    #[test]
+    fn test_identity_hasher() {
+        let state = IdentityState::default();
+        assert_eq!(state.hash_one(42u64), 42);
+        assert_eq!(state.hash_one(7u8), 7);
+        assert_ne!(state.hash_one("foo"), state.hash_one("bar"));
+
+        let mut map: IntMap<&str> = HashMap::default();
+        for id in 0..100 {
+            map.put(id, "handle");
+        }
+        assert_eq!(map.get(&42), Some(&"handle"));
+        assert_eq!(map.pop(&42), Some("handle"));
+        assert_eq!(map.get(&42), None);
+
+        // Sequential IDs spread out perfectly, one per bucket
+        assert_eq!(map.worst_case_lookup().map(|(_, length)| length), Some(1));
+    }
+
+    #[cfg(feature = "fxhash")]
+    #[test]
+    fn test_fx_hasher() {
+        check_map(HashMap::with_hasher(FxBuildHasher::default()));
+
+        // No seed, so every hasher agrees
+        let a = FxBuildHasher::default();
+        let b = FxBuildHasher::default();
+        assert_eq!(a.hash_one("foo"), b.hash_one("foo"));
+        assert_ne!(a.hash_one("foo"), a.hash_one("bar"));
+    }
+
+    #[cfg(feature = "ahash")]
+    #[test]
+    fn test_a_hasher() {
+        check_map(HashMap::with_hasher(AHashState::new()));
+
+        let fixed = AHashState::with_seeds(1, 2, 3, 4);
+        assert_eq!(fixed.hash_one("foo"), AHashState::with_seeds(1, 2, 3, 4).hash_one("foo"));
+        assert_ne!(fixed.hash_one("foo"), fixed.hash_one("bar"));
+        assert_ne!(fixed.hash_one("ab"), fixed.hash_one("ab\0"));
+
+        // Random keys differ between states
+        assert_ne!(AHashState::new(), AHashState::new());
+    }
+}
@@ -0,0 +1,139 @@
+//! Consistency checks for a [HashMap]'s internal state.
+
+use std::hash::{BuildHasher, Hash};
+
+use super::{HashMap, Slot};
+
+/// The ways a [HashMap]'s internal state can be inconsistent.
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// An entry is stored in `bucket`, but its key belongs in `expected_bucket`.
+    MisplacedEntry { bucket: usize, expected_bucket: usize },
+    /// An entry in `bucket` has a cached hash which doesn't match its key's hash.
+    StaleHash { bucket: usize },
+    /// More than one entry in `bucket` has the same key.
+    DuplicateKey { bucket: usize },
+    /// The map's recorded size doesn't match the number of entries it holds.
+    SizeMismatch { recorded: usize, actual: usize }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Checks that every entry's cached hash is its key's hash, that every entry is stored in the
+    /// bucket its key hashes to, that no key is stored more than once, and that the map's size
+    /// matches the number of entries it holds. A map which is only modified through its public
+    /// methods should always pass.
+    ///
+    /// Part way through an incremental resize, entries still in the old table are checked against
+    /// the old table's capacity, and buckets are numbered within the table they belong to.
+    pub fn validate_integrity(&self) -> Result<(), IntegrityError> {
+        self.validate_table(&self.items)?;
+        self.validate_table(&self.old_items)?;
+
+        // A key could also be in both tables at once
+        for slot in self.old_items.iter().flatten() {
+            let bucket = self.index_for_hash(slot.hash, self.capacity());
+            if self.items[bucket].iter().any(|other| other.key == slot.key) {
+                return Err(IntegrityError::DuplicateKey { bucket });
+            }
+        }
+
+        let actual = self.slots().count();
+        if actual != self.size {
+            return Err(IntegrityError::SizeMismatch { recorded: self.size, actual });
+        }
+
+        Ok(())
+    }
+
+    // Checks the hashes, placement, and uniqueness of the entries in one of the map's tables.
+    fn validate_table(&self, table: &[Vec<Slot<K, V>>]) -> Result<(), IntegrityError> {
+        for (bucket, slots) in table.iter().enumerate() {
+            for (position, slot) in slots.iter().enumerate() {
+                let hash = self.make_hash(&slot.key);
+                if hash != slot.hash {
+                    return Err(IntegrityError::StaleHash { bucket });
+                }
+
+                let expected_bucket = self.index_for_hash(hash, table.len());
+                if expected_bucket != bucket {
+                    return Err(IntegrityError::MisplacedEntry { bucket, expected_bucket });
+                }
+
+                // Every key is in its own bucket at this point, so duplicates can only be
+                // found within the same bucket
+                if slots[position + 1..].iter().any(|other| other.key == slot.key) {
+                    return Err(IntegrityError::DuplicateKey { bucket });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_map() -> HashMap<i32, i32> {
+        let mut map = HashMap::new();
+        for i in 1..100 {
+            map.put(i, i);
+        }
+        map
+    }
+
+    #[test]
+    fn test_valid_map() {
+        let mut map = make_map();
+        assert_eq!(map.validate_integrity(), Ok(()));
+
+        map.pop(&5);
+        map.resize(7);
+        assert_eq!(map.validate_integrity(), Ok(()));
+    }
+
+    #[test]
+    fn test_misplaced_entry() {
+        let mut map = make_map();
+
+        let expected_bucket = map.find_key_index(&1, map.capacity());
+        let bucket = (expected_bucket + 1) % map.capacity();
+        let position = map.items[expected_bucket].iter().position(|slot| slot.key == 1).unwrap();
+        let slot = map.items[expected_bucket].remove(position);
+        map.items[bucket].push(slot);
+
+        assert_eq!(map.validate_integrity(), Err(IntegrityError::MisplacedEntry { bucket, expected_bucket }));
+    }
+
+    #[test]
+    fn test_stale_hash() {
+        let mut map = make_map();
+
+        let bucket = map.find_key_index(&1, map.capacity());
+        let slot = map.items[bucket].iter_mut().find(|slot| slot.key == 1).unwrap();
+        slot.hash = slot.hash.wrapping_add(1);
+
+        assert_eq!(map.validate_integrity(), Err(IntegrityError::StaleHash { bucket }));
+    }
+
+    #[test]
+    fn test_duplicate_key() {
+        let mut map = make_map();
+
+        let bucket = map.find_key_index(&1, map.capacity());
+        let hash = map.make_hash(&1);
+        map.items[bucket].push(Slot { key: 1, value: 2, hash, order: 0 });
+        map.size += 1;
+
+        assert_eq!(map.validate_integrity(), Err(IntegrityError::DuplicateKey { bucket }));
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let mut map = make_map();
+
+        map.size += 1;
+
+        assert_eq!(map.validate_integrity(), Err(IntegrityError::SizeMismatch { recorded: 100, actual: 99 }));
+    }
+}
@@ -1,27 +1,55 @@
-use super::{HashMap, Entry};
+use std::{iter::{Chain, FusedIterator}, vec};
+
+use super::{HashMap, Slot};
+
+// Iterates over the buckets of one of the map's tables
+type Buckets<K, V> = vec::IntoIter<Vec<Slot<K, V>>>;
 
 /// An [Iterator] for a [HashMap] which returns its entries with ownership.
-pub struct IntoIter<'a, K, V> {
-    iterator: Box<dyn Iterator<Item = Entry<K, V>> + 'a>
+pub struct IntoIter<K, V> {
+    // The buckets which haven't been visited yet
+    buckets: Chain<Buckets<K, V>, Buckets<K, V>>,
+    // The rest of the bucket currently being visited
+    bucket: vec::IntoIter<Slot<K, V>>,
+    remaining: usize
 }
 
-impl<'a, K: 'a, V: 'a> HashMap<K, V> {
-    /// Consume this [HashMap] to produce an iterator.
-    pub fn into_iter(self) -> IntoIter<'a, K, V> {
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consume this [HashMap] to produce an [IntoIter].
+    fn into_iter(self) -> Self::IntoIter {
         IntoIter {
-            iterator: Box::new(self.items.into_iter().flatten())
+            buckets: self.items.into_iter().chain(self.old_items),
+            bucket: Default::default(),
+            remaining: self.size
         }
     }
 }
 
-impl<'a, K, V> Iterator for IntoIter<'a, K, V> {
-    type Item = Entry<K, V>;
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iterator.next()
+        loop {
+            if let Some(slot) = self.bucket.next() {
+                self.remaining -= 1;
+                return Some((slot.key, slot.value));
+            }
+            self.bucket = self.buckets.next()?.into_iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,7 +70,6 @@ mod tests {
 
         // Use iterator to pull out all items
         let mut map_items: Vec<(i32, i32)> = map.into_iter()
-            .map(|entry| (entry.key, entry.value))
             .collect();
         let entries = make_entries();
         let mut processed_entries: Vec<(i32, i32)> = entries.into_iter().collect();
@@ -53,4 +80,52 @@ mod tests {
 
         assert_eq!(map_items, processed_entries);
     }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut map = HashMap::new();
+        for entry in make_entries() {
+            map.put(entry.0, entry.1);
+        }
+
+        let mut map_items = Vec::new();
+        for (key, value) in map {
+            map_items.push((key, value));
+        }
+        map_items.sort_by_key(|entry| entry.0);
+
+        assert_eq!(map_items, make_entries());
+    }
+
+    #[test]
+    fn test_borrowed_keys() {
+        // Keys which only live as long as this test can still be iterated over with `for`
+        let words: Vec<String> = (1..10).map(|i| i.to_string()).collect();
+        let mut map = HashMap::new();
+        for word in words.iter() {
+            map.put(word.as_str(), word.len());
+        }
+
+        let mut keys = Vec::new();
+        for (key, _) in map {
+            keys.push(key);
+        }
+        keys.sort();
+        assert_eq!(keys, words);
+    }
+
+    // Storing the iterator doesn't need a lifetime
+    struct Pending {
+        entries: IntoIter<i32, i32>
+    }
+
+    #[test]
+    fn test_stored_iterator() {
+        let mut map = HashMap::new();
+        map.put(1, 10);
+
+        let mut pending = Pending { entries: map.into_iter() };
+        assert_eq!(pending.entries.next(), Some((1, 10)));
+        assert_eq!(pending.entries.next(), None);
+    }
 }
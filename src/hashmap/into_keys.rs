@@ -0,0 +1,54 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, into_iter::IntoIter};
+
+/// An [Iterator] for a [HashMap] which returns its keys with ownership.
+pub struct IntoKeys<K, V> {
+    iterator: IntoIter<K, V>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Consume this [HashMap] to produce an [IntoKeys] iterator. The values are dropped.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys { iterator: self.into_iter() }
+    }
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+impl<K, V> FusedIterator for IntoKeys<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i.to_string(), i * 10);
+        }
+
+        // Map is unordered, so sort before comparing
+        let mut keys: Vec<String> = map.into_keys().collect();
+        keys.sort();
+
+        let mut expected: Vec<String> = (1..100).map(|i| i.to_string()).collect();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+}
@@ -0,0 +1,54 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, into_iter::IntoIter};
+
+/// An [Iterator] for a [HashMap] which returns its values with ownership.
+pub struct IntoValues<K, V> {
+    iterator: IntoIter<K, V>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Consume this [HashMap] to produce an [IntoValues] iterator. The keys are dropped.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues { iterator: self.into_iter() }
+    }
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+impl<K, V> FusedIterator for IntoValues<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, (i * 10).to_string());
+        }
+
+        // Map is unordered, so sort before comparing
+        let mut values: Vec<String> = map.into_values().collect();
+        values.sort();
+
+        let mut expected: Vec<String> = (1..100).map(|i| (i * 10).to_string()).collect();
+        expected.sort();
+        assert_eq!(values, expected);
+    }
+}
@@ -1,28 +1,79 @@
-use super::{HashMap, Entry};
+use std::{iter::{Chain, FusedIterator}, slice};
+
+use super::{HashMap, Slot};
+
+// Iterates over the buckets of one of the map's tables
+type Buckets<'a, K, V> = slice::Iter<'a, Vec<Slot<K, V>>>;
 
 /// An [Iterator] for a [HashMap] which returns shared references to its entries.
-pub struct Iter<'a, 'b, K: 'a, V: 'a> {
-    iterator: Box<dyn Iterator<Item = Entry<&'a K, &'a V>> + 'b>
+pub struct Iter<'a, K, V> {
+    // The buckets which haven't been visited yet
+    buckets: Chain<Buckets<'a, K, V>, Buckets<'a, K, V>>,
+    // The rest of the bucket currently being visited
+    bucket: slice::Iter<'a, Slot<K, V>>,
+    remaining: usize
 }
 
-impl<K, V> HashMap<K, V> {
+impl<K, V, S> HashMap<K, V, S> {
     /// Get an [Iter] for this [HashMap].
-    pub fn iter(& self) -> Iter<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
         Iter {
-            iterator: Box::new(self.items.iter().flatten()
-                .map(|entry| Entry { key: &entry.key, value: &entry.value }))
+            buckets: self.items.iter().chain(self.old_items.iter()),
+            bucket: Default::default(),
+            remaining: self.size
         }
     }
+
+    /// Get an [Iter] for this [HashMap] which is guaranteed to visit buckets in index order and
+    /// each bucket's entries in the order they were added to it. This order stays the same across
+    /// calls as long as the map isn't resized and no entries are removed with [HashMap::pop],
+    /// which moves the last entry of a bucket into the removed entry's place. Use
+    /// [HashMap::remove_stable] to remove entries without disturbing the order.
+    ///
+    /// Buckets past the treeify threshold of a map created with
+    /// [HashMap::with_options_ord](super::HashMap::with_options_ord) are kept sorted by key
+    /// instead, so their entries are visited in key order rather than the order they were added.
+    ///
+    /// While a map with [incremental resizing](super::options::OptionsBuilder::incremental_resizing)
+    /// is part way through growing, the new table's buckets are visited first, followed by
+    /// whichever of the old table's buckets haven't been moved yet. Every modification moves some
+    /// entries over, so the order isn't stable again until the move is done.
+    pub fn iter_stable(&self) -> Iter<'_, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
-impl<'a, 'b, K: 'a, V: 'a> Iterator for Iter<'a, 'b, K, V> {
-    type Item = Entry<&'a K, &'a V>;
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iterator.next()
+        loop {
+            if let Some(slot) = self.bucket.next() {
+                self.remaining -= 1;
+                return Some((&slot.key, &slot.value));
+            }
+            self.bucket = self.buckets.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,7 +94,6 @@ mod tests {
 
         // Use iterator to pull out all items
         let mut map_items: Vec<(&i32, &i32)> = map.iter()
-            .map(|entry| (entry.key, entry.value))
             .collect();
         let entries = make_entries();
         let mut processed_entries: Vec<(&i32, &i32)> = entries.iter()
@@ -55,4 +105,34 @@ mod tests {
 
         assert_eq!(map_items, processed_entries);
     }
+
+    #[test]
+    fn test_sparse_map() {
+        // Mostly empty buckets, including the first and last ones
+        let mut map = HashMap::with_capacity(1000);
+        map.put(1, 10);
+        map.put(2, 20);
+
+        let mut items: Vec<(&i32, &i32)> = map.iter().collect();
+        items.sort();
+        assert_eq!(items, vec![(&1, &10), (&2, &20)]);
+
+        let empty: HashMap<i32, i32> = HashMap::with_capacity(1000);
+        assert!(empty.iter().next().is_none());
+    }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut map = HashMap::new();
+        for entry in make_entries() {
+            map.put(entry.0, entry.1);
+        }
+
+        let mut count = 0;
+        for (key, value) in &map {
+            assert_eq!(key, value);
+            count += 1;
+        }
+        assert_eq!(count, map.size());
+    }
 }
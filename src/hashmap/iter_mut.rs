@@ -1,29 +1,62 @@
-use super::{HashMap, Entry};
+use std::{iter::{Chain, FusedIterator}, slice};
+
+use super::{HashMap, Slot};
+
+// Iterates over the buckets of one of the map's tables
+type Buckets<'a, K, V> = slice::IterMut<'a, Vec<Slot<K, V>>>;
 
 /// An [Iterator] for a [HashMap] which returns references to its entries.
 /// The keys are immutable and the values are mutable.
-pub struct IterMut<'a, 'b, K, V> {
-    iterator: Box<dyn Iterator<Item = Entry<&'a K, &'a mut V>> + 'b>
+pub struct IterMut<'a, K, V> {
+    // The buckets which haven't been visited yet
+    buckets: Chain<Buckets<'a, K, V>, Buckets<'a, K, V>>,
+    // The rest of the bucket currently being visited
+    bucket: slice::IterMut<'a, Slot<K, V>>,
+    remaining: usize
 }
 
-impl<K, V> HashMap<K, V> {
+impl<K, V, S> HashMap<K, V, S> {
     /// Get an [IterMut] for this [HashMap].
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
         IterMut {
-            iterator: Box::new(self.items.iter_mut().flatten()
-                .map(|entry| Entry { key: &entry.key, value: &mut entry.value }))
+            buckets: self.items.iter_mut().chain(self.old_items.iter_mut()),
+            bucket: Default::default(),
+            remaining: self.size
         }
     }
 }
 
-impl<'a, 'b, K, V> Iterator for IterMut<'a, 'b, K, V> {
-    type Item = Entry<&'a K, &'a mut V>;
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iterator.next()
+        loop {
+            if let Some(slot) = self.bucket.next() {
+                self.remaining -= 1;
+                return Some((&slot.key, &mut slot.value));
+            }
+            self.bucket = self.buckets.next()?.iter_mut();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,7 +77,6 @@ mod tests {
 
         // Use iterator to pull out all items
         let mut map_items: Vec<(&i32, &mut i32)> = map.iter_mut()
-            .map(|entry| (entry.key, entry.value))
             .collect();
         let mut entries = make_entries();
         let mut processed_entries: Vec<(&i32, &mut i32)> = entries.iter_mut()
@@ -66,13 +98,14 @@ mod tests {
         {
             let mut iter = map.iter_mut();
             let some_entry = iter.next().unwrap();
-            *some_entry.value = 2;
+            *some_entry.1 = 2;
         }
 
         assert_eq!(map.get(&1), Some(&2));
     }
 
     #[test]
+    #[allow(unused_variables, unused_assignments)]
     fn cannot_mutate_key() {
         let mut map = HashMap::new();
 
@@ -81,9 +114,24 @@ mod tests {
         {
             let mut iter = map.iter_mut();
             let mut some_entry = iter.next().unwrap();
-            some_entry.key = &6;
+            some_entry.0 = &6;
         }
 
         assert_eq!(map.get(&1), Some(&1));
     }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut map = HashMap::new();
+        for entry in make_entries() {
+            map.put(entry.0, entry.1);
+        }
+
+        for (_, value) in &mut map {
+            *value *= 2;
+        }
+        for entry in make_entries() {
+            assert_eq!(map.get(&entry.0), Some(&(entry.1 * 2)));
+        }
+    }
 }
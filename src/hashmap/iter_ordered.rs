@@ -0,0 +1,93 @@
+use std::{hash::Hash, iter::FusedIterator};
+
+use super::{HashMap, Slot};
+
+/// An [Iterator] for a [HashMap] which returns shared references to its entries in the order
+/// they were first inserted.
+pub struct IterOrdered<'a, K, V> {
+    slots: std::vec::IntoIter<&'a Slot<K, V>>
+}
+
+impl<K: Hash + Eq, V, S> HashMap<K, V, S> {
+    /// Get an [IterOrdered] for this [HashMap]. If the map was created with the
+    /// `insertion_ordered` option, entries are returned in the order their keys were first
+    /// inserted; overwriting a key's value does not change its position. Otherwise, entries are
+    /// returned in the same order as [HashMap::iter].
+    pub fn iter_ordered(&self) -> IterOrdered<'_, K, V> {
+        let mut slots: Vec<_> = self.slots().collect();
+        // Stable sort, so unordered maps (where every order is zero) keep their bucket order
+        slots.sort_by_key(|slot| slot.order);
+
+        IterOrdered { slots: slots.into_iter() }
+    }
+}
+
+impl<'a, K, V> Iterator for IterOrdered<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots.next().map(|slot| (&slot.key, &slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.slots.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterOrdered<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterOrdered<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashmap::options::Options;
+
+    fn ordered_map() -> HashMap<i32, &'static str> {
+        HashMap::with_options(
+            Options::builder().insertion_ordered(true).build().unwrap())
+    }
+
+    #[test]
+    fn test_iterator() {
+        let mut map = ordered_map();
+
+        // Enough entries to trigger resizes along the way
+        let keys: Vec<i32> = (0..50).rev().collect();
+        for key in keys.iter() {
+            map.put(*key, "value");
+        }
+
+        let map_keys: Vec<i32> = map.iter_ordered().map(|(key, _)| *key).collect();
+        assert_eq!(map_keys, keys);
+    }
+
+    #[test]
+    fn test_overwrite_keeps_position() {
+        let mut map = ordered_map();
+
+        map.put(3, "a");
+        map.put(1, "b");
+        map.put(2, "c");
+        map.put(3, "d");
+
+        let map_items: Vec<(i32, &str)> = map.iter_ordered()
+            .map(|(key, value)| (*key, *value))
+            .collect();
+        assert_eq!(map_items, vec![(3, "d"), (1, "b"), (2, "c")]);
+    }
+
+    #[test]
+    fn test_pop_removes_position() {
+        let mut map = ordered_map();
+
+        map.put(3, "a");
+        map.put(1, "b");
+        map.put(2, "c");
+        map.pop(&1);
+        map.put(1, "e");
+
+        let map_keys: Vec<i32> = map.iter_ordered().map(|(key, _)| *key).collect();
+        assert_eq!(map_keys, vec![3, 2, 1]);
+    }
+}
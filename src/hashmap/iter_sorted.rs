@@ -0,0 +1,72 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, Slot};
+
+/// An [Iterator] for a [HashMap] which returns shared references to its entries sorted by key.
+pub struct IterSorted<'a, K, V> {
+    slots: std::vec::IntoIter<&'a Slot<K, V>>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get an [IterSorted] for this [HashMap], which returns entries in ascending key order.
+    /// The map doesn't keep its keys in order, so every entry is collected and sorted up front.
+    pub fn iter_sorted(&self) -> IterSorted<'_, K, V> where K: Ord {
+        self.iter_sorted_by_key(|key| key)
+    }
+
+    /// Get an [IterSorted] for this [HashMap], which returns entries in ascending order of the
+    /// sort key `f` computes from each key. Entries with equal sort keys are returned in the same
+    /// order as [HashMap::iter].
+    pub fn iter_sorted_by_key<'a, T: Ord, F: FnMut(&'a K) -> T>(&'a self, mut f: F) -> IterSorted<'a, K, V> {
+        let mut slots: Vec<_> = self.slots().collect();
+        slots.sort_by_key(|slot| f(&slot.key));
+
+        IterSorted { slots: slots.into_iter() }
+    }
+}
+
+impl<'a, K, V> Iterator for IterSorted<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots.next().map(|slot| (&slot.key, &slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.slots.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterSorted<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterSorted<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in (1..100).rev() {
+            map.put(i, i * 10);
+        }
+
+        let items: Vec<(i32, i32)> = map.iter_sorted().map(|(key, value)| (*key, *value)).collect();
+        let expected: Vec<(i32, i32)> = (1..100).map(|i| (i, i * 10)).collect();
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_sorted_by_key() {
+        let map = HashMap::from([("ccc", 1), ("a", 2), ("bb", 3)]);
+
+        let keys: Vec<&str> = map.iter_sorted_by_key(|key| key.len()).map(|(key, _)| *key).collect();
+        assert_eq!(keys, vec!["a", "bb", "ccc"]);
+
+        let keys: Vec<&str> = map.iter_sorted_by_key(|key| std::cmp::Reverse(*key)).map(|(key, _)| *key).collect();
+        assert_eq!(keys, vec!["ccc", "bb", "a"]);
+    }
+}
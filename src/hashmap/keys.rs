@@ -0,0 +1,53 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, iter::Iter};
+
+/// An [Iterator] for a [HashMap] which returns shared references to its keys.
+pub struct Keys<'a, K, V> {
+    iterator: Iter<'a, K, V>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get a [Keys] iterator for this [HashMap].
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iterator: self.iter() }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, i * 10);
+        }
+
+        // Map is unordered, so sort before comparing
+        let mut keys: Vec<&i32> = map.keys().collect();
+        keys.sort();
+
+        let expected: Vec<i32> = (1..100).collect();
+        assert_eq!(keys, expected.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,406 @@
+//! An API for inspecting and updating the entry for a single key in a [HashMap] with one lookup.
+
+use std::{borrow::Borrow, hash::{BuildHasher, Hash}, mem};
+
+use super::{HashMap, DefaultState};
+
+/// A view into the place in a [HashMap] for a single key, which may or may not be occupied.
+/// Created with [HashMap::entry].
+pub enum MapEntry<'a, K, V, S = DefaultState> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>)
+}
+
+/// A [MapEntry] for a key which is in the map.
+pub struct OccupiedEntry<'a, K, V, S = DefaultState> {
+    map: &'a mut HashMap<K, V, S>,
+    index: usize,
+    position: usize
+}
+
+/// A [MapEntry] for a key which isn't in the map.
+pub struct VacantEntry<'a, K, V, S = DefaultState> {
+    map: &'a mut HashMap<K, V, S>,
+    key: K,
+    // The key's hash, from looking it up
+    hash: u64
+}
+
+/// Like [MapEntry], but for a borrowed form of the key. An owned key is only built from the
+/// borrowed one if a value is actually inserted. Created with [HashMap::entry_ref].
+pub enum EntryRef<'a, 'b, K, Q: ?Sized, V, S = DefaultState> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntryRef<'a, 'b, K, Q, V, S>)
+}
+
+/// An [EntryRef] for a key which isn't in the map.
+pub struct VacantEntryRef<'a, 'b, K, Q: ?Sized, V, S = DefaultState> {
+    map: &'a mut HashMap<K, V, S>,
+    key: &'b Q,
+    // The borrowed key's hash, which the owned key built from it shares
+    hash: u64
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Gets the [MapEntry] for a key, which can then be used to read, insert, update, or remove
+    /// its value without looking the key up again.
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V, S> {
+        match self.locate(&key) {
+            (_, index, Some(position)) => MapEntry::Occupied(OccupiedEntry { map: self, index, position }),
+            (hash, _, None) => MapEntry::Vacant(VacantEntry { map: self, key, hash })
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    /// Gets the [EntryRef] for a borrowed form of a key, e.g. `&str` for a map with [String]
+    /// keys. Unlike [HashMap::entry], this doesn't need an owned key up front, so looking up an
+    /// occupied entry never has to allocate one.
+    pub fn entry_ref<'b, Q>(&mut self, key: &'b Q) -> EntryRef<'_, 'b, K, Q, V, S> where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let hash = self.make_hash(key);
+        match self.locate_borrowed_hashed(hash, key) {
+            (index, Some(position)) => EntryRef::Occupied(OccupiedEntry { map: self, index, position }),
+            (_, None) => EntryRef::Vacant(VacantEntryRef { map: self, key, hash })
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> MapEntry<'a, K, V, S> {
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key()
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant. Returns a reference to the entry's value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant. Returns a reference to the
+    /// entry's value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    /// Inserts the value type's default if the entry is vacant. Returns a reference to the
+    /// entry's value.
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    /// Updates the entry's value with `f` if it's occupied, then returns the entry.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let MapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> OccupiedEntry<'a, K, V, S> {
+    /// Returns a reference to the key stored in the map.
+    pub fn key(&self) -> &K {
+        &self.map.items[self.index][self.position].key
+    }
+
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        &self.map.items[self.index][self.position].value
+    }
+
+    /// Returns a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.items[self.index][self.position].value
+    }
+
+    /// Converts the entry into a mutable reference to its value which lives as long as the map
+    /// is borrowed.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.items[self.index][self.position].value
+    }
+
+    /// Replaces the entry's value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Replaces both the stored key and the value, returning the old pair. This is useful when
+    /// keys carry data which their [Eq] implementation ignores. `key` must be equal to the
+    /// stored key, or the map will no longer be able to find the entry.
+    pub fn replace_entry(self, key: K, value: V) -> (K, V) {
+        let slot = &mut self.map.items[self.index][self.position];
+        (mem::replace(&mut slot.key, key), mem::replace(&mut slot.value, value))
+    }
+
+    /// Removes the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        self.map.size -= 1;
+        let value = self.map.remove_at(self.index, self.position).value;
+        self.map.shrink_if_sparse();
+        value
+    }
+}
+
+impl<'a, 'b, K, Q, V, S: BuildHasher> EntryRef<'a, 'b, K, Q, V, S> where K: Hash + Eq + Borrow<Q> + From<&'b Q>, Q: ?Sized {
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &Q {
+        match self {
+            EntryRef::Occupied(entry) => entry.key().borrow(),
+            EntryRef::Vacant(entry) => entry.key()
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant. Returns a reference to the entry's value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant. Returns a reference to the
+    /// entry's value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            EntryRef::Occupied(entry) => entry.into_mut(),
+            EntryRef::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    /// Inserts the value type's default if the entry is vacant. Returns a reference to the
+    /// entry's value.
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    /// Updates the entry's value with `f` if it's occupied, then returns the entry.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let EntryRef::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    /// Returns a reference to the key which would be inserted.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back ownership of the key without inserting anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key with `value`, returning a reference to the inserted value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_new_hashed(self.hash, self.key, value)
+    }
+}
+
+impl<'a, 'b, K, Q, V, S: BuildHasher> VacantEntryRef<'a, 'b, K, Q, V, S> where K: Hash + Eq + From<&'b Q>, Q: ?Sized {
+    /// Returns a reference to the borrowed key which would be inserted.
+    pub fn key(&self) -> &'b Q {
+        self.key
+    }
+
+    /// Builds an owned key from the borrowed one and inserts it with `value`, returning a
+    /// reference to the inserted value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_new_hashed(self.hash, K::from(self.key), value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use crate::hashmap::hashers::CountingState;
+
+    use super::*;
+
+    #[test]
+    fn test_or_insert() {
+        let mut map = HashMap::new();
+
+        *map.entry("foo").or_insert(1) += 10;
+        assert_eq!(map.get(&"foo"), Some(&11));
+
+        *map.entry("foo").or_insert(1) += 10;
+        assert_eq!(map.get(&"foo"), Some(&21));
+        assert_eq!(map.size(), 1);
+    }
+
+    #[test]
+    fn test_or_default() {
+        let mut map: HashMap<&str, Vec<i32>> = HashMap::new();
+
+        map.entry("foo").or_default().push(1);
+        map.entry("foo").or_default().push(2);
+        assert_eq!(map.get(&"foo"), Some(&vec![1, 2]));
+
+        let mut counts: HashMap<String, i32> = HashMap::new();
+        *counts.entry_ref("bar").or_default() += 1;
+        *counts.entry_ref("bar").or_default() += 1;
+        assert_eq!(counts.get("bar"), Some(&2));
+    }
+
+    #[test]
+    fn test_and_modify() {
+        let mut map = HashMap::new();
+
+        for word in ["a", "b", "a"] {
+            map.entry(word).and_modify(|count| *count += 1).or_insert_with(|| 1);
+        }
+
+        assert_eq!(map.get(&"a"), Some(&2));
+        assert_eq!(map.get(&"b"), Some(&1));
+    }
+
+    #[test]
+    fn test_occupied_entry() {
+        let mut map = HashMap::new();
+        map.put("foo", 1);
+
+        match map.entry("foo") {
+            MapEntry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &"foo");
+                assert_eq!(entry.get(), &1);
+                assert_eq!(entry.insert(2), 1);
+                assert_eq!(entry.remove(), 2);
+            },
+            MapEntry::Vacant(_) => panic!("Entry should be occupied")
+        }
+
+        assert_eq!(map.get(&"foo"), None);
+        assert_eq!(map.size(), 0);
+    }
+
+    #[test]
+    fn test_vacant_entry() {
+        let mut map = HashMap::new();
+
+        match map.entry("foo") {
+            MapEntry::Vacant(entry) => assert_eq!(entry.into_key(), "foo"),
+            MapEntry::Occupied(_) => panic!("Entry should be vacant")
+        }
+        assert_eq!(map.size(), 0);
+
+        match map.entry("foo") {
+            MapEntry::Vacant(entry) => *entry.insert(1) += 1,
+            MapEntry::Occupied(_) => panic!("Entry should be vacant")
+        }
+        assert_eq!(map.get(&"foo"), Some(&2));
+    }
+
+    // A key whose tag isn't part of its identity
+    #[derive(Debug)]
+    struct TaggedKey {
+        id: i32,
+        tag: &'static str
+    }
+
+    impl PartialEq for TaggedKey {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for TaggedKey {}
+
+    impl Hash for TaggedKey {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.id.hash(state);
+        }
+    }
+
+    #[test]
+    fn test_replace_entry() {
+        let mut map = HashMap::new();
+        map.put(TaggedKey { id: 1, tag: "old" }, "first");
+
+        let (old_key, old_value) = match map.entry(TaggedKey { id: 1, tag: "lookup" }) {
+            MapEntry::Occupied(entry) => entry.replace_entry(TaggedKey { id: 1, tag: "new" }, "second"),
+            MapEntry::Vacant(_) => panic!("Entry should be occupied")
+        };
+        assert_eq!(old_key.tag, "old");
+        assert_eq!(old_value, "first");
+
+        match map.entry(TaggedKey { id: 1, tag: "lookup" }) {
+            MapEntry::Occupied(entry) => {
+                assert_eq!(entry.key().tag, "new");
+                assert_eq!(entry.get(), &"second");
+            },
+            MapEntry::Vacant(_) => panic!("Entry should be occupied")
+        }
+        assert_eq!(map.size(), 1);
+    }
+
+    // A string key which counts how many times it has been built from a borrowed `&str`
+    #[derive(PartialEq, Eq, Hash)]
+    struct CountedKey(String);
+
+    thread_local! {
+        static KEYS_BUILT: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl From<&str> for CountedKey {
+        fn from(key: &str) -> Self {
+            KEYS_BUILT.with(|built| built.set(built.get() + 1));
+            CountedKey(key.to_string())
+        }
+    }
+
+    impl std::borrow::Borrow<str> for CountedKey {
+        fn borrow(&self) -> &str {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_entry_ref() {
+        let mut map: HashMap<CountedKey, i32> = HashMap::new();
+
+        for word in ["a", "b", "a", "a"] {
+            map.entry_ref(word).and_modify(|count| *count += 1).or_insert(1);
+        }
+
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map.get("b"), Some(&1));
+        // Keys were only built for the two vacant entries
+        assert_eq!(KEYS_BUILT.with(|built| built.get()), 2);
+    }
+
+    #[test]
+    fn test_vacant_insert_hashes_once() {
+        let mut map: HashMap<String, i32, CountingState> = HashMap::with_hasher(CountingState::default());
+
+        map.entry(String::from("a")).or_insert(1);
+        assert_eq!(map.hasher().0.get(), 1);
+        map.entry_ref("b").or_insert(2);
+        assert_eq!(map.hasher().0.get(), 2);
+
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn test_entry_ref_key() {
+        let mut map: HashMap<String, i32> = HashMap::new();
+        map.put(String::from("foo"), 1);
+
+        assert_eq!(map.entry_ref("foo").key(), "foo");
+        match map.entry_ref("bar") {
+            EntryRef::Vacant(entry) => {
+                assert_eq!(entry.key(), "bar");
+                *entry.insert(2) += 1;
+            },
+            EntryRef::Occupied(_) => panic!("Entry should be vacant")
+        }
+        assert_eq!(map.get("bar"), Some(&3));
+    }
+}
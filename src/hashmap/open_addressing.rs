@@ -0,0 +1,532 @@
+//! A hash map which uses open addressing rather than separate chaining. Every entry lives in a
+//! single flat array of buckets, so there's no allocation per bucket and lookups walk memory
+//! which is next to each other. Collisions are resolved by linear probing: a key which finds
+//! its bucket taken tries the next one along, wrapping around at the end. Removed entries
+//! leave a tombstone behind so the keys probing past them can still be found.
+//!
+//! Since every entry needs a bucket of its own, the map can never be fuller than
+//! [MAX_LOAD_FACTOR], whatever its options say.
+
+use std::{fmt, hash::{BuildHasher, Hash}, iter::FusedIterator, mem, slice};
+
+use super::{DefaultState, equivalent::Equivalent, options::{self, Options}};
+
+/// The highest load factor an [OpenHashMap] will use. Linear probing slows down sharply as the
+/// table fills up, and the table must always keep an empty bucket for lookups to stop at.
+pub const MAX_LOAD_FACTOR: f64 = 0.9;
+
+/// A hash map object which stores its entries in a single array with linear probing. It's
+/// created from the same [Options] as a [HashMap](super::HashMap), but only the initial
+/// capacity, load factor, growth factor, lazy buckets, and Fibonacci hashing options apply.
+/// It always resizes as needed, since it can't hold more entries than it has buckets.
+pub struct OpenHashMap<K, V, S = DefaultState> {
+    buckets: Vec<Bucket<K, V>>,
+    size: usize,
+    // Buckets whose entries were removed, which still have to be probed past
+    tombstones: usize,
+    resize_count: usize,
+    options: Options,
+    hash_builder: S
+}
+
+#[derive(Clone)]
+enum Bucket<K, V> {
+    Empty,
+    Tombstone,
+    Full { key: K, value: V, hash: u64 }
+}
+
+// Where probing for a key ended up.
+enum Probe {
+    // The key is in the bucket at this index.
+    Found(usize),
+    // The key isn't in the map, and would be inserted at this index.
+    Vacant(usize)
+}
+
+impl<K, V> OpenHashMap<K, V> {
+    /// Creates a new [OpenHashMap] with the default options. Nothing is allocated until the
+    /// first entry is put in the map.
+    pub const fn new() -> Self {
+        OpenHashMap {
+            buckets: Vec::new(),
+            size: 0,
+            tombstones: 0,
+            resize_count: 0,
+            options: Options::DEFAULT,
+            hash_builder: DefaultState::new()
+        }
+    }
+
+    /// Creates a new [OpenHashMap] with the given options. See the type's documentation for
+    /// which of them apply.
+    pub fn with_options(options: Options) -> Self {
+        OpenHashMap::with_options_and_hasher(options, DefaultState::default())
+    }
+}
+
+impl<K, V, S> OpenHashMap<K, V, S> {
+    /// Creates a new [OpenHashMap] with the default options which hashes keys with hashers built
+    /// by `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        OpenHashMap::with_options_and_hasher(Options::default(), hash_builder)
+    }
+
+    /// Creates a new [OpenHashMap] with the given options which hashes keys with hashers built by
+    /// `hash_builder`.
+    pub fn with_options_and_hasher(options: Options, hash_builder: S) -> Self {
+        let buckets = if options.lazy_buckets() {
+            Vec::new()
+        } else {
+            Self::create_buckets(options.initial_capacity())
+        };
+        OpenHashMap { buckets, size: 0, tombstones: 0, resize_count: 0, options, hash_builder }
+    }
+
+    fn create_buckets(capacity: usize) -> Vec<Bucket<K, V>> {
+        let mut buckets = Vec::with_capacity(capacity);
+        buckets.resize_with(capacity, || Bucket::Empty);
+        buckets
+    }
+
+    /// Returns a reference to the map's [BuildHasher].
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the current number of entries in the map.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the number of buckets in the map, which is always more than its size.
+    pub fn capacity(&self) -> usize {
+        if self.buckets.is_empty() {
+            self.options.initial_capacity()
+        } else {
+            self.buckets.len()
+        }
+    }
+
+    /// Returns how many times the map has been resized since it was created. Clearing out
+    /// tombstones rebuilds the table too, so it counts as well.
+    pub fn resize_count(&self) -> usize {
+        self.resize_count
+    }
+
+    /// Removes every entry from the map. The map keeps its capacity.
+    pub fn clear(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            *bucket = Bucket::Empty;
+        }
+        self.size = 0;
+        self.tombstones = 0;
+    }
+
+    /// Get an [Iter] for this [OpenHashMap].
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { buckets: self.buckets.iter(), remaining: self.size }
+    }
+
+    /// Rebuilds the table with `capacity` buckets, which also clears out any tombstones. Keys
+    /// aren't hashed again, since each entry keeps its hash.
+    ///
+    /// Panics if `capacity` isn't more than the map's size, since every entry needs a bucket and
+    /// there must be an empty one left over.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > self.size, "An OpenHashMap needs more buckets than entries");
+
+        let old = mem::replace(&mut self.buckets, Self::create_buckets(capacity));
+        for bucket in old {
+            if let Bucket::Full { hash, .. } = bucket {
+                let index = self.first_free(hash);
+                self.buckets[index] = bucket;
+            }
+        }
+        self.tombstones = 0;
+        self.resize_count += 1;
+    }
+
+    // Finds the first bucket which isn't full, starting from the bucket `hash` picks.
+    fn first_free(&self, hash: u64) -> usize {
+        let mut index = self.index_for_hash(hash);
+        while let Bucket::Full { .. } = self.buckets[index] {
+            index = (index + 1) % self.buckets.len();
+        }
+        index
+    }
+
+    fn index_for_hash(&self, hash: u64) -> usize {
+        if self.options.fibonacci_hashing() {
+            return options::fibonacci_index(hash, self.buckets.len());
+        }
+        // "as" here is fine since we're truncating the hash with the modulo anyway
+        hash as usize % self.buckets.len()
+    }
+
+    fn load_factor_at(&self, capacity: usize) -> f64 {
+        self.options.load_factor_at(capacity).min(MAX_LOAD_FACTOR)
+    }
+
+    // Makes room for one more entry, first allocating the table if it doesn't exist yet. Since
+    // tombstones take up buckets just like entries, the table is rebuilt once they'd push it over
+    // its load factor, and only grows if the entries alone would. Returns whether the table was
+    // allocated or rebuilt, which moves entries to different buckets.
+    fn reserve_one(&mut self) -> bool {
+        let allocated = self.buckets.is_empty();
+        if allocated {
+            self.buckets = Self::create_buckets(self.options.initial_capacity());
+        }
+
+        let threshold = self.capacity() as f64 * self.load_factor_at(self.capacity());
+        if (self.size + self.tombstones + 1) as f64 >= threshold {
+            let mut capacity = self.capacity();
+            while (self.size + 1) as f64 >= capacity as f64 * self.load_factor_at(capacity) {
+                capacity = (capacity as f64 * self.options.growth_factor()).ceil() as usize;
+            }
+            self.resize(capacity);
+            return true;
+        }
+        allocated
+    }
+
+    // Empties the bucket at `index`, returning its entry. A tombstone is only needed if a key
+    // could have probed past this bucket, which it can't have if the next bucket is empty.
+    fn take_at(&mut self, index: usize) -> (K, V) {
+        let next = (index + 1) % self.buckets.len();
+        let replacement = if let Bucket::Empty = self.buckets[next] {
+            Bucket::Empty
+        } else {
+            self.tombstones += 1;
+            Bucket::Tombstone
+        };
+
+        self.size -= 1;
+        match mem::replace(&mut self.buckets[index], replacement) {
+            Bucket::Full { key, value, .. } => (key, value),
+            _ => unreachable!("Only full buckets can be taken")
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> OpenHashMap<K, V, S> {
+    /// Gets a reference to the value corresponding to a key, if it exists. Like
+    /// [HashMap::get](super::HashMap::get), the key may be any borrowed form of the map's key
+    /// type, or any other type which is [Equivalent] to it.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V> where Q: Hash + Equivalent<K> + ?Sized {
+        match self.probe(key) {
+            Some(Probe::Found(index)) => match &self.buckets[index] {
+                Bucket::Full { value, .. } => Some(value),
+                _ => unreachable!("Probing only finds full buckets")
+            },
+            _ => None
+        }
+    }
+
+    /// Gets a mutable reference to the value corresponding to a key, if it exists.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V> where Q: Hash + Equivalent<K> + ?Sized {
+        match self.probe(key) {
+            Some(Probe::Found(index)) => match &mut self.buckets[index] {
+                Bucket::Full { value, .. } => Some(value),
+                _ => unreachable!("Probing only finds full buckets")
+            },
+            _ => None
+        }
+    }
+
+    /// Returns whether the map has an entry for a key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool where Q: Hash + Equivalent<K> + ?Sized {
+        matches!(self.probe(key), Some(Probe::Found(_)))
+    }
+
+    /// Puts a `(key, value)` pair in the map. This will overwrite any existing value for the given
+    /// key. Returns the existing value if it exists.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash_builder.hash_one(&key);
+        let vacant = match (!self.buckets.is_empty()).then(|| self.probe_hash(hash, &key)) {
+            Some(Probe::Found(index)) => match &mut self.buckets[index] {
+                Bucket::Full { value: existing, .. } => return Some(mem::replace(existing, value)),
+                _ => unreachable!("Probing only finds full buckets")
+            },
+            Some(Probe::Vacant(index)) => Some(index),
+            None => None
+        };
+
+        // Only resize once the key is known to be new. A rebuilt table has no tombstones and the
+        // key isn't in it, so the first free bucket is where the key goes without probing again
+        let rebuilt = self.reserve_one();
+        let index = match vacant {
+            Some(index) if !rebuilt => index,
+            _ => self.first_free(hash)
+        };
+
+        if let Bucket::Tombstone = self.buckets[index] {
+            self.tombstones -= 1;
+        }
+        self.buckets[index] = Bucket::Full { key, value, hash };
+        self.size += 1;
+        None
+    }
+
+    /// Returns the value corresponding to a key, if it exists, removing it from the map.
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V> where Q: Hash + Equivalent<K> + ?Sized {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the map, returning the stored key and its value if the key was present.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)> where Q: Hash + Equivalent<K> + ?Sized {
+        match self.probe(key) {
+            Some(Probe::Found(index)) => Some(self.take_at(index)),
+            _ => None
+        }
+    }
+
+    // Probes for `key`, or returns [None] if the table hasn't been allocated yet.
+    fn probe<Q>(&self, key: &Q) -> Option<Probe> where Q: Hash + Equivalent<K> + ?Sized {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        Some(self.probe_hash(self.hash_builder.hash_one(key), key))
+    }
+
+    // Walks the buckets from the one `hash` picks until it finds `key` or an empty bucket. A new
+    // key is placed in the first tombstone passed along the way, if there was one, so removed
+    // entries' buckets get reused.
+    fn probe_hash<Q>(&self, hash: u64, key: &Q) -> Probe where Q: Equivalent<K> + ?Sized {
+        let mut index = self.index_for_hash(hash);
+        let mut tombstone = None;
+        loop {
+            match &self.buckets[index] {
+                Bucket::Empty => return Probe::Vacant(tombstone.unwrap_or(index)),
+                Bucket::Tombstone => {
+                    tombstone.get_or_insert(index);
+                },
+                // Comparing hashes first usually saves comparing keys which merely share a probe
+                Bucket::Full { key: other, hash: other_hash, .. } => {
+                    if *other_hash == hash && key.equivalent(other) {
+                        return Probe::Found(index);
+                    }
+                }
+            }
+            index = (index + 1) % self.buckets.len();
+        }
+    }
+}
+
+impl<K, V> Default for OpenHashMap<K, V> {
+    fn default() -> Self {
+        OpenHashMap::new()
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for OpenHashMap<K, V, S> {
+    /// Copies the map bucket by bucket, tombstones included, so no entries need to be rehashed.
+    fn clone(&self) -> Self {
+        OpenHashMap {
+            buckets: self.buckets.clone(),
+            size: self.size,
+            tombstones: self.tombstones,
+            resize_count: self.resize_count,
+            options: self.options.clone(),
+            hash_builder: self.hash_builder.clone()
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for OpenHashMap<K, V, S> {
+    /// Formats the map like `{"key": value, ...}`, in the same order as [OpenHashMap::iter].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for OpenHashMap<K, V, S> {
+    /// Puts every `(key, value)` pair into the map.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.put(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for OpenHashMap<K, V> {
+    /// Creates a new [OpenHashMap] with the default options from `(key, value)` pairs. Later
+    /// pairs overwrite earlier ones with the same key.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OpenHashMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+/// An [Iterator] for an [OpenHashMap] which returns shared references to its entries, in
+/// bucket order.
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Bucket<K, V>>,
+    remaining: usize
+}
+
+impl<'a, K, V, S> IntoIterator for &'a OpenHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for bucket in self.buckets.by_ref() {
+            if let Bucket::Full { key, value, .. } = bucket {
+                self.remaining -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::hashmap::hashers::{CountingState, IdentityState};
+
+    use super::*;
+
+    #[test]
+    fn test_put_get_pop() {
+        let mut map = OpenHashMap::new();
+        for i in 0..100 {
+            assert_eq!(map.put(i, i * 10), None);
+        }
+        assert_eq!(map.size(), 100);
+        assert_eq!(map.put(5, 0), Some(50));
+
+        for i in 0..100 {
+            assert!(map.contains_key(&i));
+        }
+        assert_eq!(map.get(&5), Some(&0));
+        assert_eq!(map.get(&100), None);
+
+        for i in 0..50 {
+            assert!(map.pop(&i).is_some());
+        }
+        assert_eq!(map.size(), 50);
+        assert_eq!(map.get(&10), None);
+        assert_eq!(map.get(&60), Some(&600));
+        assert_eq!(map.iter().count(), 50);
+    }
+
+    #[test]
+    fn test_put_hashes_once() {
+        let options = Options::builder().initial_capacity(4).build().unwrap();
+        let mut map = OpenHashMap::with_options_and_hasher(options, CountingState::default());
+
+        // Including the puts which allocate or grow the table
+        for i in 0..20 {
+            map.put(i, i);
+            assert_eq!(map.hasher().0.get(), i + 1);
+        }
+        assert_eq!(map.put(5, 50), Some(5));
+        assert_eq!(map.hasher().0.get(), 21);
+        assert!(map.resize_count() > 0);
+        for i in 0..20 {
+            assert!(map.contains_key(&i));
+        }
+    }
+
+    #[test]
+    fn test_unallocated() {
+        let map: OpenHashMap<i32, i32> = OpenHashMap::new();
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.capacity(), options::DEFAULT_CAPACITY);
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn test_probing_past_tombstones() {
+        // With identity hashing, these keys all start probing from bucket 1
+        let mut map = OpenHashMap::with_hasher(IdentityState::default());
+        map.put(1u64, "a");
+        map.put(17, "b");
+        map.put(33, "c");
+
+        // 33 is still found past the tombstone 17 leaves behind
+        assert_eq!(map.pop(&17), Some("b"));
+        assert_eq!(map.tombstones, 1);
+        assert_eq!(map.get(&33), Some(&"c"));
+
+        // And the next colliding key reuses the tombstone
+        map.put(49, "d");
+        assert_eq!(map.tombstones, 0);
+        assert!(matches!(map.buckets[2], Bucket::Full { key: 49, .. }));
+
+        // 33 comes after 49, so removing 49 leaves a tombstone, but the last key in a run doesn't
+        // need one
+        assert_eq!(map.pop(&49), Some("d"));
+        assert_eq!(map.pop(&33), Some("c"));
+        assert_eq!(map.tombstones, 1);
+        assert_eq!(map.get(&1), Some(&"a"));
+    }
+
+    #[test]
+    fn test_growth() {
+        let options = Options::builder().initial_capacity(4).load_factor(0.5).build().unwrap();
+        let mut map = OpenHashMap::with_options(options);
+        for i in 0..1000 {
+            map.put(i.to_string(), i);
+        }
+        assert!(map.resize_count() > 0);
+        assert!(map.size() as f64 <= map.capacity() as f64 * 0.5);
+        for i in 0..1000 {
+            assert_eq!(map.get(i.to_string().as_str()), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_tombstones_are_cleared() {
+        let mut map = OpenHashMap::new();
+        for i in 0..10 {
+            map.put(i, i);
+        }
+        let capacity = map.capacity();
+
+        // Churning through new keys never grows the map, only rebuilds it
+        for i in 10..10_000 {
+            map.put(i, i);
+            map.pop(&(i - 10));
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.size(), 10);
+        assert!((map.size() + map.tombstones) as f64 <= capacity as f64 * map.load_factor_at(capacity));
+        for i in 9990..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_load_factor_capped() {
+        let options = Options::builder().initial_capacity(10).load_factor(2.0).build().unwrap();
+        let mut map = OpenHashMap::with_options(options);
+        for i in 0..100 {
+            map.put(i, i);
+        }
+        assert!(map.capacity() > map.size());
+        assert_eq!(map.get(&99), Some(&99));
+    }
+}
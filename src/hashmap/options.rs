@@ -1,50 +1,261 @@
 //! HashMap configuration options and validation.
 
+use std::{error::Error, fmt};
+
 pub const DEFAULT_CAPACITY: usize = 16;
 pub const DEFAULT_LOAD_FACTOR: f64 = 0.75;
 pub const DEFAULT_DYNAMIC_RESIZING: bool = true;
+pub const DEFAULT_INSERTION_ORDERED: bool = false;
+pub const DEFAULT_ADAPTIVE_LOAD_FACTOR: bool = false;
+pub const DEFAULT_GROWTH_FACTOR: f64 = 2.0;
+pub const DEFAULT_LAZY_BUCKETS: bool = true;
+pub const DEFAULT_FIBONACCI_HASHING: bool = false;
+pub const DEFAULT_AUTO_SHRINK: bool = true;
+pub const DEFAULT_INCREMENTAL_RESIZING: bool = false;
 
-/// An unvalidated set of hash map options. Create an [Options]
-/// and call `validate` to produce a [ValidatedOptions] which can then be used
-/// to create a hash map. Properties left as [None] will be set to sensible defaults.
-#[derive(Default)]
-pub struct Options {
-    pub initial_capacity: Option<usize>,
-    pub load_factor: Option<f64>,
-    pub dynamic_resizing: Option<bool>
+/// How full a map can get, as a fraction of its load factor, before removing entries makes it
+/// shrink. Only applies to maps with auto shrinking and dynamic resizing enabled.
+pub const SHRINK_THRESHOLD: f64 = 0.25;
+
+/// How many of the old table's buckets each operation moves into the new one while a map with
+/// incremental resizing enabled is part way through growing. Operations move more than this when
+/// the map is close to growing again, so that the move is always done before the next one starts.
+pub const INCREMENTAL_RESIZE_STEP: usize = 8;
+
+/// Capacity up to which an adaptive load factor is the same as the configured one.
+pub const ADAPTIVE_SMALL_CAPACITY: usize = 1 << 8;
+/// Capacity from which an adaptive load factor stops decreasing.
+pub const ADAPTIVE_LARGE_CAPACITY: usize = 1 << 16;
+/// How much of the configured load factor is left at [ADAPTIVE_LARGE_CAPACITY] and beyond.
+pub const ADAPTIVE_MIN_SCALE: f64 = 2.0 / 3.0;
+
+/// Computes the load factor to use at `capacity` when adaptive load factors are enabled.
+/// Up to [ADAPTIVE_SMALL_CAPACITY] this is just `load_factor`. From there it decreases steadily
+/// with each doubling of capacity until it reaches `load_factor * ADAPTIVE_MIN_SCALE` at
+/// [ADAPTIVE_LARGE_CAPACITY], so the default of 0.75 ends up at 0.5 for large maps.
+pub fn adaptive_load_factor(load_factor: f64, capacity: usize) -> f64 {
+    let small = (ADAPTIVE_SMALL_CAPACITY as f64).log2();
+    let large = (ADAPTIVE_LARGE_CAPACITY as f64).log2();
+    let capacity = (capacity.clamp(ADAPTIVE_SMALL_CAPACITY, ADAPTIVE_LARGE_CAPACITY) as f64).log2();
+
+    let progress = (capacity - small) / (large - small);
+    load_factor * (1.0 - progress * (1.0 - ADAPTIVE_MIN_SCALE))
+}
+
+/// 2^64 divided by the golden ratio, rounded to an odd number.
+pub const FIBONACCI_MULTIPLIER: u64 = 11400714819323198485;
+
+/// Picks which of `capacity` buckets a key with `hash` belongs in by Fibonacci hashing. The hash
+/// is multiplied by [FIBONACCI_MULTIPLIER], which mixes every bit of it into the high bits of the
+/// product, and those high bits are then scaled down to the capacity. For a power of two
+/// capacity this is the same as keeping the top bits of the product.
+pub fn fibonacci_index(hash: u64, capacity: usize) -> usize {
+    let scrambled = hash.wrapping_mul(FIBONACCI_MULTIPLIER);
+    ((scrambled as u128 * capacity as u128) >> 64) as usize
+}
+
+/// A builder for [Options]. Create one with [Options::builder], set whichever options should
+/// differ from the defaults, then call `build` to validate them, e.g.
+/// `Options::builder().load_factor(0.9).build()`.
+#[derive(Default, Clone)]
+pub struct OptionsBuilder {
+    initial_capacity: Option<usize>,
+    load_factor: Option<f64>,
+    dynamic_resizing: Option<bool>,
+    insertion_ordered: Option<bool>,
+    treeify_threshold: Option<usize>,
+    adaptive_load_factor: Option<bool>,
+    growth_factor: Option<f64>,
+    lazy_buckets: Option<bool>,
+    fibonacci_hashing: Option<bool>,
+    auto_shrink: Option<bool>,
+    incremental_resizing: Option<bool>
 }
 
-pub struct ValidatedOptions {
+/// The reasons an [OptionsBuilder] can fail to build.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionsError {
+    /// The initial capacity was zero.
+    ZeroCapacity,
+    /// The load factor was zero or less.
+    InvalidLoadFactor(f64),
+    /// The growth factor was one or less, so resizing wouldn't grow the map.
+    InvalidGrowthFactor(f64)
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::ZeroCapacity => write!(f, "Initial capacity cannot be zero"),
+            OptionsError::InvalidLoadFactor(lf) => write!(f, "Load factor cannot be zero or less, got {lf}"),
+            OptionsError::InvalidGrowthFactor(gf) => write!(f, "Growth factor must be greater than one, got {gf}")
+        }
+    }
+}
+
+impl Error for OptionsError {}
+
+/// A validated set of hash map options, which can be used to create a hash map. Create one with
+/// [Options::builder], or use [Options::default] for the defaults.
+#[derive(Clone)]
+pub struct Options {
     initial_capacity: usize,
     load_factor: f64,
-    dynamic_resizing: bool
+    dynamic_resizing: bool,
+    insertion_ordered: bool,
+    treeify_threshold: Option<usize>,
+    adaptive_load_factor: bool,
+    growth_factor: f64,
+    lazy_buckets: bool,
+    fibonacci_hashing: bool,
+    auto_shrink: bool,
+    incremental_resizing: bool
 }
 
-impl Options {
-    /// Validates an [Options] to produce a [ValidatedOptions] or a list of errors.
-    pub fn validate(self) -> Result<ValidatedOptions, Vec<&'static str>> {
-        let mut errors = Vec::new();
-
-        self.load_factor.map(|lf| {
-            if lf <= 0.0 {
-                errors.push("Load factor cannot be zero or less");
-            };
-        });
-
-        if errors.is_empty() {
-            Ok(ValidatedOptions {
-                initial_capacity: self.initial_capacity.unwrap_or(DEFAULT_CAPACITY),
-                load_factor: self.load_factor.unwrap_or(DEFAULT_LOAD_FACTOR),
-                dynamic_resizing: self.dynamic_resizing.unwrap_or(DEFAULT_DYNAMIC_RESIZING)
-            })
-        } else {
-            Err(errors)
+impl OptionsBuilder {
+    /// The number of buckets the map starts with.
+    pub fn initial_capacity(mut self, initial_capacity: usize) -> Self {
+        self.initial_capacity = Some(initial_capacity);
+        self
+    }
+
+    /// How full the map can get, as entries per bucket, before it grows.
+    pub fn load_factor(mut self, load_factor: f64) -> Self {
+        self.load_factor = Some(load_factor);
+        self
+    }
+
+    /// Whether the map grows by itself to keep to its load factor.
+    pub fn dynamic_resizing(mut self, dynamic_resizing: bool) -> Self {
+        self.dynamic_resizing = Some(dynamic_resizing);
+        self
+    }
+
+    /// Whether the map remembers the order its keys were inserted in.
+    pub fn insertion_ordered(mut self, insertion_ordered: bool) -> Self {
+        self.insertion_ordered = Some(insertion_ordered);
+        self
+    }
+
+    /// Buckets with more entries than this are kept sorted so they can be binary searched.
+    /// Requires the map to be created with `HashMap::with_options_ord`. Disabled by default.
+    pub fn treeify_threshold(mut self, treeify_threshold: usize) -> Self {
+        self.treeify_threshold = Some(treeify_threshold);
+        self
+    }
+
+    /// Whether to lower the load factor as the map grows. See [adaptive_load_factor].
+    pub fn adaptive_load_factor(mut self, adaptive_load_factor: bool) -> Self {
+        self.adaptive_load_factor = Some(adaptive_load_factor);
+        self
+    }
+
+    /// How much to multiply the capacity by when dynamically resizing.
+    pub fn growth_factor(mut self, growth_factor: f64) -> Self {
+        self.growth_factor = Some(growth_factor);
+        self
+    }
+
+    /// Whether to put off allocating the map's buckets until the first insertion.
+    pub fn lazy_buckets(mut self, lazy_buckets: bool) -> Self {
+        self.lazy_buckets = Some(lazy_buckets);
+        self
+    }
+
+    /// Whether to pick buckets by Fibonacci hashing rather than taking the hash modulo the
+    /// capacity. This spreads out poor quality hashes, like sequential integers through a weak
+    /// hasher, much better. See [fibonacci_index].
+    pub fn fibonacci_hashing(mut self, fibonacci_hashing: bool) -> Self {
+        self.fibonacci_hashing = Some(fibonacci_hashing);
+        self
+    }
+
+    /// Whether removing entries shrinks the map once it's much emptier than its load factor
+    /// allows. Only applies when dynamic resizing is enabled too. See [SHRINK_THRESHOLD].
+    pub fn auto_shrink(mut self, auto_shrink: bool) -> Self {
+        self.auto_shrink = Some(auto_shrink);
+        self
+    }
+
+    /// Whether growing the map keeps the old table around and moves its entries into the new one
+    /// a few buckets at a time, as the map is modified, rather than all at once. This bounds how
+    /// long any single insertion takes, at the cost of lookups checking both tables until the
+    /// move is done. Since the move has to finish before the map grows again, how many buckets
+    /// each step moves depends on the growth factor: close to 1, the map grows again after only a
+    /// few insertions, so each step moves a large share of the table. Only applies to dynamic
+    /// resizing; see [INCREMENTAL_RESIZE_STEP].
+    pub fn incremental_resizing(mut self, incremental_resizing: bool) -> Self {
+        self.incremental_resizing = Some(incremental_resizing);
+        self
+    }
+
+    /// Validates the options to produce [Options], or the first reason they're invalid. Options
+    /// which weren't set are given sensible defaults.
+    pub fn build(self) -> Result<Options, OptionsError> {
+        if self.initial_capacity == Some(0) {
+            return Err(OptionsError::ZeroCapacity);
+        }
+
+        if let Some(lf) = self.load_factor {
+            check_load_factor(lf)?;
+        }
+
+        if let Some(gf) = self.growth_factor {
+            if gf.is_nan() || gf <= 1.0 {
+                return Err(OptionsError::InvalidGrowthFactor(gf));
+            }
         }
 
+        Ok(Options {
+            initial_capacity: self.initial_capacity.unwrap_or(DEFAULT_CAPACITY),
+            load_factor: self.load_factor.unwrap_or(DEFAULT_LOAD_FACTOR),
+            dynamic_resizing: self.dynamic_resizing.unwrap_or(DEFAULT_DYNAMIC_RESIZING),
+            insertion_ordered: self.insertion_ordered.unwrap_or(DEFAULT_INSERTION_ORDERED),
+            treeify_threshold: self.treeify_threshold,
+            adaptive_load_factor: self.adaptive_load_factor.unwrap_or(DEFAULT_ADAPTIVE_LOAD_FACTOR),
+            growth_factor: self.growth_factor.unwrap_or(DEFAULT_GROWTH_FACTOR),
+            lazy_buckets: self.lazy_buckets.unwrap_or(DEFAULT_LAZY_BUCKETS),
+            fibonacci_hashing: self.fibonacci_hashing.unwrap_or(DEFAULT_FIBONACCI_HASHING),
+            auto_shrink: self.auto_shrink.unwrap_or(DEFAULT_AUTO_SHRINK),
+            incremental_resizing: self.incremental_resizing.unwrap_or(DEFAULT_INCREMENTAL_RESIZING)
+        })
+    }
+}
+
+fn check_load_factor(lf: f64) -> Result<(), OptionsError> {
+    if lf.is_nan() || lf <= 0.0 {
+        return Err(OptionsError::InvalidLoadFactor(lf));
+    }
+    Ok(())
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options::DEFAULT
     }
 }
 
-impl ValidatedOptions {
+impl Options {
+    // The default options, available in const contexts such as `HashMap::new`
+    pub(crate) const DEFAULT: Options = Options {
+        initial_capacity: DEFAULT_CAPACITY,
+        load_factor: DEFAULT_LOAD_FACTOR,
+        dynamic_resizing: DEFAULT_DYNAMIC_RESIZING,
+        insertion_ordered: DEFAULT_INSERTION_ORDERED,
+        treeify_threshold: None,
+        adaptive_load_factor: DEFAULT_ADAPTIVE_LOAD_FACTOR,
+        growth_factor: DEFAULT_GROWTH_FACTOR,
+        lazy_buckets: DEFAULT_LAZY_BUCKETS,
+        fibonacci_hashing: DEFAULT_FIBONACCI_HASHING,
+        auto_shrink: DEFAULT_AUTO_SHRINK,
+        incremental_resizing: DEFAULT_INCREMENTAL_RESIZING
+    };
+
+    /// Creates an [OptionsBuilder] with every option unset.
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
     pub fn initial_capacity(&self) -> usize {
         self.initial_capacity
     }
@@ -56,6 +267,59 @@ impl ValidatedOptions {
     pub fn dynamic_resizing(&self) -> bool {
         self.dynamic_resizing
     }
+
+    pub fn insertion_ordered(&self) -> bool {
+        self.insertion_ordered
+    }
+
+    pub fn treeify_threshold(&self) -> Option<usize> {
+        self.treeify_threshold
+    }
+
+    pub fn adaptive_load_factor(&self) -> bool {
+        self.adaptive_load_factor
+    }
+
+    pub fn growth_factor(&self) -> f64 {
+        self.growth_factor
+    }
+
+    pub fn lazy_buckets(&self) -> bool {
+        self.lazy_buckets
+    }
+
+    pub fn fibonacci_hashing(&self) -> bool {
+        self.fibonacci_hashing
+    }
+
+    pub fn auto_shrink(&self) -> bool {
+        self.auto_shrink
+    }
+
+    pub fn incremental_resizing(&self) -> bool {
+        self.incremental_resizing
+    }
+
+    // Changes the load factor of a map which has already been created, validating it the same
+    // way as the builder does.
+    pub(crate) fn set_load_factor(&mut self, load_factor: f64) -> Result<(), OptionsError> {
+        check_load_factor(load_factor)?;
+        self.load_factor = load_factor;
+        Ok(())
+    }
+
+    pub(crate) fn set_dynamic_resizing(&mut self, dynamic_resizing: bool) {
+        self.dynamic_resizing = dynamic_resizing;
+    }
+
+    /// The load factor a map with these options should use at `capacity`.
+    pub fn load_factor_at(&self, capacity: usize) -> f64 {
+        if self.adaptive_load_factor {
+            adaptive_load_factor(self.load_factor, capacity)
+        } else {
+            self.load_factor
+        }
+    }
 }
 
 #[cfg(test)]
@@ -64,23 +328,84 @@ mod tests {
 
     #[test]
     fn passing_validation() {
-        let options = Options {
-            initial_capacity: Some(DEFAULT_CAPACITY),
-            load_factor: Some(DEFAULT_LOAD_FACTOR),
-            dynamic_resizing: Some(DEFAULT_DYNAMIC_RESIZING)
-        };
+        let options = Options::builder()
+            .initial_capacity(DEFAULT_CAPACITY)
+            .load_factor(DEFAULT_LOAD_FACTOR)
+            .dynamic_resizing(DEFAULT_DYNAMIC_RESIZING)
+            .insertion_ordered(DEFAULT_INSERTION_ORDERED)
+            .adaptive_load_factor(DEFAULT_ADAPTIVE_LOAD_FACTOR)
+            .growth_factor(DEFAULT_GROWTH_FACTOR)
+            .lazy_buckets(DEFAULT_LAZY_BUCKETS)
+            .fibonacci_hashing(DEFAULT_FIBONACCI_HASHING)
+            .auto_shrink(DEFAULT_AUTO_SHRINK)
+            .incremental_resizing(DEFAULT_INCREMENTAL_RESIZING)
+            .build();
 
-        assert!(options.validate().is_ok());
+        assert!(options.is_ok());
+    }
+
+    #[test]
+    fn initial_capacity_invalid() {
+        let options = Options::builder().initial_capacity(0).build();
+
+        assert_eq!(options.err(), Some(OptionsError::ZeroCapacity));
     }
 
     #[test]
     fn load_factor_invalid() {
-        let options = Options {
-            initial_capacity: Some(DEFAULT_CAPACITY),
-            load_factor: Some(-0.5),
-            dynamic_resizing: Some(DEFAULT_DYNAMIC_RESIZING)
-        };
+        let options = Options::builder()
+            .initial_capacity(DEFAULT_CAPACITY)
+            .load_factor(-0.5)
+            .build();
+
+        assert_eq!(options.err(), Some(OptionsError::InvalidLoadFactor(-0.5)));
+        assert!(Options::builder().load_factor(f64::NAN).build().is_err());
+    }
+
+    #[test]
+    fn growth_factor_invalid() {
+        for growth_factor in [1.0, 0.5, -2.0] {
+            let options = Options::builder().growth_factor(growth_factor).build();
+
+            assert_eq!(options.err(), Some(OptionsError::InvalidGrowthFactor(growth_factor)));
+        }
+
+        assert!(Options::builder().growth_factor(1.1).build().is_ok());
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(OptionsError::ZeroCapacity.to_string(), "Initial capacity cannot be zero");
+        assert_eq!(OptionsError::InvalidLoadFactor(-0.5).to_string(), "Load factor cannot be zero or less, got -0.5");
+    }
+
+    #[test]
+    fn fibonacci_index_range() {
+        for capacity in [1, 7, 16, 1000] {
+            for hash in [0, 1, 2, u64::MAX / 3, u64::MAX] {
+                assert!(fibonacci_index(hash, capacity) < capacity);
+            }
+        }
 
-        assert!(options.validate().is_err());
+        // Sequential hashes land far apart
+        assert_ne!(fibonacci_index(1, 16), fibonacci_index(2, 16));
+        assert_eq!(fibonacci_index(0, 16), 0);
+    }
+
+    #[test]
+    fn adaptive_load_factor_range() {
+        let load_factor = DEFAULT_LOAD_FACTOR;
+
+        assert_eq!(adaptive_load_factor(load_factor, 1), load_factor);
+        assert_eq!(adaptive_load_factor(load_factor, ADAPTIVE_SMALL_CAPACITY), load_factor);
+        assert!((adaptive_load_factor(load_factor, ADAPTIVE_LARGE_CAPACITY) - 0.5).abs() < 1e-9);
+        assert!((adaptive_load_factor(load_factor, usize::MAX) - 0.5).abs() < 1e-9);
+
+        // Decreases steadily in between
+        let mut capacity = ADAPTIVE_SMALL_CAPACITY;
+        while capacity < ADAPTIVE_LARGE_CAPACITY {
+            assert!(adaptive_load_factor(load_factor, capacity * 2) < adaptive_load_factor(load_factor, capacity));
+            capacity *= 2;
+        }
     }
 }
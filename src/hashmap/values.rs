@@ -0,0 +1,53 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, iter::Iter};
+
+/// An [Iterator] for a [HashMap] which returns shared references to its values.
+pub struct Values<'a, K, V> {
+    iterator: Iter<'a, K, V>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get a [Values] iterator for this [HashMap].
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iterator: self.iter() }
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, i * 10);
+        }
+
+        // Map is unordered, so sort before comparing
+        let mut values: Vec<&i32> = map.values().collect();
+        values.sort();
+
+        let expected: Vec<i32> = (1..100).map(|i| i * 10).collect();
+        assert_eq!(values, expected.iter().collect::<Vec<_>>());
+    }
+}
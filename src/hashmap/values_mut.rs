@@ -0,0 +1,54 @@
+use std::iter::FusedIterator;
+
+use super::{HashMap, iter_mut::IterMut};
+
+/// An [Iterator] for a [HashMap] which returns mutable references to its values.
+pub struct ValuesMut<'a, K, V> {
+    iterator: IterMut<'a, K, V>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get a [ValuesMut] iterator for this [HashMap].
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { iterator: self.iter_mut() }
+    }
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut map = HashMap::new();
+
+        // Make sure multiple buckets in map are filled
+        for i in 1..100 {
+            map.put(i, i);
+        }
+
+        for value in map.values_mut() {
+            *value *= 10;
+        }
+
+        for i in 1..100 {
+            assert_eq!(map.get(&i), Some(&(i * 10)));
+        }
+    }
+}
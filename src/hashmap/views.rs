@@ -0,0 +1,151 @@
+//! Read-only views of a [HashMap]'s keys and values, which borrow the map rather than copying
+//! anything out of it.
+
+use std::hash::{BuildHasher, Hash};
+
+use super::{HashMap, DefaultState, equivalent::Equivalent, keys::Keys, values::Values};
+
+/// A view of the keys in a [HashMap], which can be queried and compared like a set.
+/// Created with [HashMap::keys_view].
+pub struct KeysView<'a, K, V, S = DefaultState> {
+    map: &'a HashMap<K, V, S>
+}
+
+/// A view of the values in a [HashMap]. Created with [HashMap::values_view].
+pub struct ValuesView<'a, K, V, S = DefaultState> {
+    map: &'a HashMap<K, V, S>
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Get a [KeysView] for this [HashMap].
+    pub fn keys_view(&self) -> KeysView<'_, K, V, S> {
+        KeysView { map: self }
+    }
+
+    /// Get a [ValuesView] for this [HashMap].
+    pub fn values_view(&self) -> ValuesView<'_, K, V, S> {
+        ValuesView { map: self }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> KeysView<'a, K, V, S> {
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.map.size()
+    }
+
+    /// Returns whether the map has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns whether the map has `key`. Like [HashMap::get], the key may be any borrowed form
+    /// of the map's key type.
+    pub fn contains<Q>(&self, key: &Q) -> bool where Q: Hash + Equivalent<K> + ?Sized {
+        self.map.contains_key(key)
+    }
+
+    /// Get a [Keys] iterator for the map.
+    pub fn iter(&self) -> Keys<'a, K, V> {
+        self.map.keys()
+    }
+
+    /// Returns whether every key in this view is also in `other`.
+    pub fn is_subset<W, T: BuildHasher>(&self, other: &KeysView<'_, K, W, T>) -> bool {
+        self.len() <= other.len() && self.iter().all(|key| other.contains(key))
+    }
+
+    /// Returns whether every key in `other` is also in this view.
+    pub fn is_superset<W, T: BuildHasher>(&self, other: &KeysView<'_, K, W, T>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns whether this view and `other` have no keys in common.
+    pub fn is_disjoint<W, T: BuildHasher>(&self, other: &KeysView<'_, K, W, T>) -> bool {
+        // Look up the smaller view's keys in the larger one
+        if self.len() <= other.len() {
+            self.iter().all(|key| !other.contains(key))
+        } else {
+            other.iter().all(|key| !self.contains(key))
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, W, S: BuildHasher, T: BuildHasher> PartialEq<KeysView<'_, K, W, T>> for KeysView<'a, K, V, S> {
+    /// Views are equal when their maps have exactly the same keys, whatever their values.
+    fn eq(&self, other: &KeysView<'_, K, W, T>) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<'a, K, V, S> ValuesView<'a, K, V, S> {
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.map.size
+    }
+
+    /// Returns whether the map has no values.
+    pub fn is_empty(&self) -> bool {
+        self.map.size == 0
+    }
+
+    /// Returns whether any key in the map has `value`. Values aren't hashed, so this is a
+    /// linear scan over every entry in the map.
+    pub fn contains(&self, value: &V) -> bool where V: PartialEq {
+        self.iter().any(|other| other == value)
+    }
+
+    /// Get a [Values] iterator for the map.
+    pub fn iter(&self) -> Values<'a, K, V> {
+        self.map.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_view() {
+        let map = HashMap::from([(String::from("a"), 1), (String::from("b"), 2)]);
+        let keys = map.keys_view();
+
+        assert_eq!(keys.len(), 2);
+        assert!(!keys.is_empty());
+        assert!(keys.contains("a"));
+        assert!(!keys.contains("c"));
+
+        let mut collected: Vec<&String> = keys.iter().collect();
+        collected.sort();
+        assert_eq!(collected, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_keys_view_set_operations() {
+        let small = HashMap::from([(1, "one"), (2, "two")]);
+        let large = HashMap::from([(1, 1.0), (2, 2.0), (3, 3.0)]);
+        let other = HashMap::from([(4, ())]);
+
+        assert!(small.keys_view().is_subset(&large.keys_view()));
+        assert!(!large.keys_view().is_subset(&small.keys_view()));
+        assert!(large.keys_view().is_superset(&small.keys_view()));
+        assert!(small.keys_view().is_disjoint(&other.keys_view()));
+        assert!(!small.keys_view().is_disjoint(&large.keys_view()));
+
+        assert!(small.keys_view() != large.keys_view());
+        let same_keys = HashMap::from([(2, 'b'), (1, 'a')]);
+        assert!(small.keys_view() == same_keys.keys_view());
+    }
+
+    #[test]
+    fn test_values_view() {
+        let map = HashMap::from([("a", 1), ("b", 2), ("c", 2)]);
+        let values = map.values_view();
+
+        assert_eq!(values.len(), 3);
+        assert!(!values.is_empty());
+        assert!(values.contains(&2));
+        assert!(!values.contains(&3));
+        assert_eq!(values.iter().sum::<i32>(), 5);
+    }
+}
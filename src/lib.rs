@@ -1,3 +1,5 @@
 pub mod linked_list;
 pub mod persistent_list;
 pub mod hashmap;
+pub mod set;
+pub mod collection;
@@ -1,3 +1,5 @@
+use crate::persistent_list;
+
 pub mod into_iter;
 pub mod iter;
 pub mod iter_mut;
@@ -25,7 +27,7 @@ impl<T> LinkedList<T> {
     /// Pushes an item to the head of the list.
     pub fn push(&mut self, item: T) {
         let new_node = Box::new(Node {
-            item: item,
+            item,
             next: self.head.take()
         });
 
@@ -44,6 +46,67 @@ impl<T> LinkedList<T> {
         self.pop_link().map(|node| node.item)
     }
 
+    /// Removes up to `n` items from the head of the list and returns them in the order they
+    /// were removed. Stops early if the list runs out of items.
+    pub fn pop_n(&mut self, n: usize) -> Vec<T> {
+        let mut items = Vec::new();
+        while items.len() < n {
+            match self.pop() {
+                Some(item) => items.push(item),
+                None => break
+            }
+        }
+        items
+    }
+
+    /// Moves the first `n` items of the list to its tail, keeping their order. Rotating by
+    /// the list's length or more wraps around, so rotating by `n` is the same as rotating by
+    /// `n % len`.
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.iter().count();
+        if len == 0 || n.is_multiple_of(len) {
+            return;
+        }
+
+        // Detach the first n nodes
+        let mut front = self.head.take();
+        let mut front_end = front.as_mut().unwrap();
+        for _ in 1..n % len {
+            front_end = front_end.next.as_mut().unwrap();
+        }
+        self.head = front_end.next.take();
+
+        // Reattach them after the last node
+        let mut tail = &mut self.head;
+        while tail.is_some() {
+            tail = &mut tail.as_mut().unwrap().next;
+        }
+        *tail = front;
+    }
+
+    /// Removes consecutive repeated items, keeping the first of each run, like [Vec::dedup].
+    /// Equal items which aren't next to each other are kept.
+    pub fn dedup(&mut self) where T: PartialEq {
+        let mut current = self.head.as_mut();
+        while let Some(node) = current {
+            while node.next.as_ref().is_some_and(|next| next.item == node.item) {
+                // Unlink the duplicate before it's dropped so it doesn't drop the rest of the list
+                let mut duplicate = node.next.take().unwrap();
+                node.next = duplicate.next.take();
+            }
+            current = node.next.as_mut();
+        }
+    }
+
+    /// Consumes the list to build an immutable, persistent list with the same items in the same
+    /// order. Items are moved rather than cloned.
+    pub fn into_persistent(self) -> persistent_list::LinkedList<T> {
+        // The persistent list can only be built from the tail up
+        let items: Vec<T> = self.into_iter().collect();
+        items.into_iter().rev()
+            .fold(persistent_list::LinkedList::new(), |list, item| list.prepend(item))
+    }
+
     /// Returns a shared reference to the list's head, if it exists.
     pub fn peek(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.item)
@@ -55,10 +118,16 @@ impl<T> LinkedList<T> {
     }
 }
 
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        LinkedList::new()
+    }
+}
+
 // https://rust-unofficial.github.io/too-many-lists/first-drop.html
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop_link() {}
+        while self.pop_link().is_some() {}
     }
 }
 
@@ -110,11 +179,109 @@ mod tests {
 
         list.push(1);
 
-        list.peek_mut()
-            .map(|value| *value = 2);
+        if let Some(value) = list.peek_mut() {
+            *value = 2;
+        }
         assert_eq!(list.peek_mut(), Some(&mut 2));
 
         list.pop();
         assert_eq!(list.peek_mut(), None);
     }
+
+    #[test]
+    fn test_pop_n() {
+        let mut list = LinkedList::<i32>::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        list.push(4);
+
+        assert_eq!(list.pop_n(2), vec![4, 3]);
+        assert_eq!(list.peek(), Some(&2));
+
+        assert_eq!(list.pop_n(2), vec![2, 1]);
+        assert_eq!(list.peek(), None);
+    }
+
+    #[test]
+    fn test_pop_n_more_than_available() {
+        let mut list = LinkedList::<i32>::new();
+        list.push(1);
+        list.push(2);
+
+        assert_eq!(list.pop_n(5), vec![2, 1]);
+        assert_eq!(list.pop(), None);
+
+        assert_eq!(list.pop_n(3), vec![]);
+    }
+
+    #[test]
+    fn test_rotate_left() {
+        let mut list = LinkedList::<i32>::new();
+        list.push(3);
+        list.push(2);
+        list.push(1);
+
+        list.rotate_left(1);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &3, &1]);
+
+        // Rotating by the length is a full cycle
+        list.rotate_left(3);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &3, &1]);
+
+        list.rotate_left(5);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    }
+
+    #[test]
+    fn test_rotate_left_short_lists() {
+        let mut list = LinkedList::<i32>::new();
+        list.rotate_left(2);
+        assert_eq!(list.peek(), None);
+
+        list.push(1);
+        list.rotate_left(2);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1]);
+    }
+
+    fn list_of(items: &[i32]) -> LinkedList<i32> {
+        let mut list = LinkedList::new();
+        for item in items.iter().rev() {
+            list.push(*item);
+        }
+        list
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut list = list_of(&[1, 1, 2, 3, 3, 3, 4]);
+        list.dedup();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4]);
+
+        // Only adjacent duplicates are removed
+        let mut list = list_of(&[1, 2, 1, 2, 2, 1]);
+        list.dedup();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &1, &2, &1]);
+    }
+
+    #[test]
+    fn test_dedup_edge_cases() {
+        let mut list = list_of(&[5, 5, 5, 5]);
+        list.dedup();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&5]);
+
+        let mut list = list_of(&[]);
+        list.dedup();
+        assert_eq!(list.peek(), None);
+    }
+
+    #[test]
+    fn test_into_persistent() {
+        let list = list_of(&[1, 2, 3]);
+
+        let persistent = list.into_persistent();
+        assert_eq!(persistent.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+        assert_eq!(list_of(&[]).into_persistent().head(), None);
+    }
 }
@@ -5,11 +5,16 @@ pub struct Iter<'a, T> {
 }
 
 impl<T> LinkedList<T> {
-    pub fn iter(&self) -> Iter<T>{
+    pub fn iter(&self) -> Iter<'_, T>{
         Iter {
             next: self.head.as_deref()
         }
     }
+
+    /// Returns an iterator over the list's items along with their zero-based position from the head.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.iter().enumerate()
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -40,4 +45,17 @@ mod tests {
         assert_eq!(iter.next(), Some(&1));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_iter_indexed() {
+        let mut list = LinkedList::<i32>::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.iter_indexed().collect::<Vec<_>>(), vec![(0, &3), (1, &2), (2, &1)]);
+
+        let list = LinkedList::<i32>::new();
+        assert_eq!(list.iter_indexed().next(), None);
+    }
 }
@@ -5,7 +5,7 @@ pub struct IterMut<'a, T> {
 }
 
 impl<T> LinkedList<T> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut()
         }
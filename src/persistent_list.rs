@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{ptr, rc::Rc};
 
 pub mod iter;
 
@@ -24,7 +24,7 @@ impl<T> LinkedList<T> {
     /// Creates a new list from the current one with the item prepended to the beginning.
     pub fn prepend(&self, item: T) -> Self {
         let new_node = Rc::new(Node {
-            item: item,
+            item,
             next: self.head.clone()
         });
 
@@ -40,6 +40,68 @@ impl<T> LinkedList<T> {
     pub fn head(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.item)
     }
+
+    /// Splits the list into a reference to its head and a new list of the rest of its items,
+    /// or returns [None] if the list is empty. The new list shares its nodes with this one.
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.head.as_ref().map(|node| (&node.item, LinkedList { head: node.next.clone() }))
+    }
+
+    /// Returns how many references there are to the list's head node, or zero if the list is
+    /// empty. Each list whose first node is this one counts, as does each node pointing to it,
+    /// so this shows how much `prepend` and `tail` share rather than copy.
+    pub fn head_share_count(&self) -> usize {
+        self.head.as_ref().map_or(0, Rc::strong_count)
+    }
+
+    /// Checks that following the list from its head eventually reaches the end rather than
+    /// looping back on itself. Lists built with this module's API can never contain a cycle, so
+    /// this always returns true for them; it exists as a safety check for code which might
+    /// construct nodes some other way.
+    pub fn is_acyclic(&self) -> bool {
+        is_acyclic_by(self.head.as_deref(), |node| node.next.as_deref(), |a, b| ptr::eq(a, b))
+    }
+
+    /// Collects references to every item in the list, from head to tail, so they can be
+    /// accessed by index. Items are borrowed from the list rather than cloned.
+    pub fn as_refs(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+
+    /// Collects the list's items into a sorted vector. The items may be shared with other lists,
+    /// so they are cloned rather than moved, which is why `T` must be [Clone].
+    // Takes &self since consuming one handle wouldn't give ownership of shared items anyway
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_sorted_vec(&self) -> Vec<T> where T: Ord + Clone {
+        let mut items: Vec<T> = self.iter().cloned().collect();
+        items.sort();
+        items
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        LinkedList::new()
+    }
+}
+
+// Floyd's tortoise and hare: walks a chain of nodes at two speeds, which will only meet if the
+// chain loops. `same` decides whether two nodes are the same node, rather than just equal.
+fn is_acyclic_by<N: Copy>(start: Option<N>, next: impl Fn(N) -> Option<N>, same: impl Fn(N, N) -> bool) -> bool {
+    let mut slow = start;
+    let mut fast = start;
+    loop {
+        fast = match fast.and_then(&next).and_then(&next) {
+            Some(node) => Some(node),
+            None => return true
+        };
+        slow = slow.and_then(&next);
+        if let (Some(slow), Some(fast)) = (slow, fast) {
+            if same(slow, fast) {
+                return false;
+            }
+        }
+    }
 }
 
 impl<T> Drop for LinkedList<T> {
@@ -83,4 +145,90 @@ mod tests {
         assert_eq!(list.head(), None);
 
     }
+
+    #[test]
+    fn test_as_refs() {
+        let list = LinkedList::new().prepend(1).prepend(2).prepend(3);
+
+        let refs = list.as_refs();
+        assert_eq!(refs, list.iter().collect::<Vec<_>>());
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs[0], &3);
+        assert_eq!(refs[1], &2);
+        assert_eq!(refs[2], &1);
+
+        assert!(LinkedList::<i32>::new().as_refs().is_empty());
+    }
+
+    #[test]
+    fn test_into_sorted_vec() {
+        let list = LinkedList::new().prepend(3).prepend(1).prepend(4).prepend(1).prepend(5);
+
+        assert_eq!(list.into_sorted_vec(), vec![1, 1, 3, 4, 5]);
+
+        // The list itself is untouched
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&5, &1, &4, &1, &3]);
+    }
+
+    #[test]
+    fn test_uncons() {
+        let list = LinkedList::new().prepend(1).prepend(2).prepend(3);
+
+        let expected: Vec<i32> = list.iter().copied().collect();
+
+        let mut heads = Vec::new();
+        let mut rest = list;
+        while let Some((head, tail)) = rest.uncons() {
+            heads.push(*head);
+            rest = tail;
+        }
+
+        assert_eq!(heads, expected);
+        assert!(LinkedList::<i32>::new().uncons().is_none());
+    }
+
+    #[test]
+    fn test_head_share_count() {
+        let base = LinkedList::new().prepend(1);
+        assert_eq!(base.head_share_count(), 1);
+
+        // Each of these lists points at the base's head node
+        let a = base.prepend(2);
+        let b = base.prepend(3);
+        assert_eq!(base.head_share_count(), 3);
+        assert_eq!(a.head_share_count(), 1);
+
+        // So does a tail taken from one of them
+        let c = a.tail();
+        assert_eq!(base.head_share_count(), 4);
+        assert_eq!(c.head_share_count(), 4);
+
+        drop(a);
+        drop(b);
+        assert_eq!(base.head_share_count(), 2);
+
+        assert_eq!(LinkedList::<i32>::new().head_share_count(), 0);
+    }
+
+    #[test]
+    fn test_is_acyclic() {
+        assert!(LinkedList::<i32>::new().is_acyclic());
+        assert!(LinkedList::new().prepend(1).is_acyclic());
+        assert!(LinkedList::new().prepend(1).prepend(2).prepend(3).tail().is_acyclic());
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        // The list's nodes can't be made to loop, so check the algorithm against a chain
+        // described by each node's successor instead
+        let chain = |successors: Vec<Option<usize>>| {
+            is_acyclic_by(Some(0), |node: usize| successors[node], |a, b| a == b)
+        };
+
+        assert!(chain(vec![None]));
+        assert!(chain(vec![Some(1), Some(2), None]));
+        assert!(!chain(vec![Some(0)]));
+        assert!(!chain(vec![Some(1), Some(0)]));
+        assert!(!chain(vec![Some(1), Some(2), Some(3), Some(4), Some(2)]));
+    }
 }
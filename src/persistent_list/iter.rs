@@ -5,7 +5,7 @@ pub struct Iter<'a, T> {
 }
 
 impl<T> LinkedList<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter { next: self.head.as_deref() }
     }
 }
@@ -0,0 +1,196 @@
+//! A hash set implementation built on top of [HashMap]. Every value in the set is stored as a
+//! key in the underlying map, so the set shares the map's hashing and resizing behaviour.
+
+use std::{hash::Hash, ops::{BitAnd, BitOr, Sub}};
+
+use crate::hashmap::HashMap;
+
+pub mod iter;
+
+/// A hash set object.
+pub struct HashSet<T> {
+    map: HashMap<T, ()>
+}
+
+impl<T: Hash + Eq> HashSet<T> {
+    /// Creates a new, empty [HashSet].
+    pub fn new() -> Self {
+        HashSet { map: HashMap::new() }
+    }
+
+    /// Adds a value to the set. Returns whether the value was newly added.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.put(value, ()).is_none()
+    }
+
+    /// Returns whether the set contains a value.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Removes a value from the set. Returns whether the value was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.map.pop(value).is_some()
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.map.size()
+    }
+
+    /// Returns whether the set has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a new set with the values that are in either this set or `other`.
+    pub fn union(&self, other: &Self) -> Self where T: Clone {
+        let mut set = self.difference(other);
+        for value in other.iter() {
+            set.insert(value.clone());
+        }
+        set
+    }
+
+    /// Returns a new set with the values that are in both this set and `other`.
+    pub fn intersection(&self, other: &Self) -> Self where T: Clone {
+        self.filtered(|value| other.contains(value))
+    }
+
+    /// Returns a new set with the values that are in this set but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self where T: Clone {
+        self.filtered(|value| !other.contains(value))
+    }
+
+    fn filtered<F: Fn(&T) -> bool>(&self, predicate: F) -> Self where T: Clone {
+        let mut set = HashSet::new();
+        for value in self.iter().filter(|value| predicate(value)) {
+            set.insert(value.clone());
+        }
+        set
+    }
+}
+
+impl<T: Hash + Eq + Clone> BitOr for &HashSet<T> {
+    type Output = HashSet<T>;
+
+    /// See [HashSet::union].
+    fn bitor(self, other: Self) -> HashSet<T> {
+        self.union(other)
+    }
+}
+
+impl<T: Hash + Eq + Clone> BitAnd for &HashSet<T> {
+    type Output = HashSet<T>;
+
+    /// See [HashSet::intersection].
+    fn bitand(self, other: Self) -> HashSet<T> {
+        self.intersection(other)
+    }
+}
+
+impl<T: Hash + Eq + Clone> Sub for &HashSet<T> {
+    type Output = HashSet<T>;
+
+    /// See [HashSet::difference].
+    fn sub(self, other: Self) -> HashSet<T> {
+        self.difference(other)
+    }
+}
+
+impl<T: Hash + Eq> Default for HashSet<T> {
+    fn default() -> Self {
+        HashSet::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_contains() {
+        let mut set = HashSet::new();
+
+        assert!(!set.contains(&"foo"));
+
+        assert!(set.insert("foo"));
+        assert!(set.contains(&"foo"));
+
+        // Inserting the same value again doesn't add it twice
+        assert!(!set.insert("foo"));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = HashSet::new();
+
+        set.insert("foo");
+        assert!(set.remove(&"foo"));
+        assert!(!set.contains(&"foo"));
+        assert!(!set.remove(&"foo"));
+    }
+
+    fn set_of(values: &[i32]) -> HashSet<i32> {
+        let mut set = HashSet::new();
+        for value in values {
+            set.insert(*value);
+        }
+        set
+    }
+
+    fn sorted(set: HashSet<i32>) -> Vec<i32> {
+        let mut values: Vec<i32> = set.iter().copied().collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_disjoint_sets() {
+        let a = set_of(&[1, 2]);
+        let b = set_of(&[3, 4]);
+
+        assert_eq!(sorted(a.union(&b)), vec![1, 2, 3, 4]);
+        assert_eq!(sorted(a.intersection(&b)), vec![]);
+        assert_eq!(sorted(a.difference(&b)), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_overlapping_sets() {
+        let a = set_of(&[1, 2, 3]);
+        let b = set_of(&[2, 3, 4]);
+
+        assert_eq!(sorted(&a | &b), vec![1, 2, 3, 4]);
+        assert_eq!(sorted(&a & &b), vec![2, 3]);
+        assert_eq!(sorted(&a - &b), vec![1]);
+        assert_eq!(sorted(&b - &a), vec![4]);
+    }
+
+    #[test]
+    fn test_subset() {
+        let a = set_of(&[1, 2, 3, 4]);
+        let b = set_of(&[2, 3]);
+
+        assert_eq!(sorted(a.union(&b)), vec![1, 2, 3, 4]);
+        assert_eq!(sorted(a.intersection(&b)), vec![2, 3]);
+        assert_eq!(sorted(a.difference(&b)), vec![1, 4]);
+        assert_eq!(sorted(b.difference(&a)), vec![]);
+    }
+
+    #[test]
+    fn test_len() {
+        let mut set = HashSet::new();
+
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+
+        set.insert(1);
+        set.insert(2);
+        assert_eq!(set.len(), 2);
+        assert!(!set.is_empty());
+
+        set.remove(&1);
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::hashmap;
+
+use super::HashSet;
+
+/// An [Iterator] for a [HashSet] which returns shared references to its values.
+pub struct Iter<'a, T> {
+    iterator: hashmap::keys::Keys<'a, T, ()>
+}
+
+impl<T> HashSet<T> {
+    /// Get an [Iter] for this [HashSet].
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { iterator: self.map.keys() }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator() {
+        let mut set = HashSet::new();
+
+        // Make sure multiple buckets in the underlying map are filled
+        for value in 1..100 {
+            set.insert(value);
+        }
+
+        // Set is unordered, so sort before comparing
+        let mut values: Vec<&i32> = set.iter().collect();
+        values.sort();
+
+        let expected: Vec<i32> = (1..100).collect();
+        assert_eq!(values, expected.iter().collect::<Vec<_>>());
+    }
+}